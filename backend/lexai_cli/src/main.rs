@@ -0,0 +1,228 @@
+//! Headless CLI for LexAI's termbase, built on the same `lexai_core` logic
+//! the desktop app uses, so the same `lexai.db` can be imported, exported,
+//! and reviewed from CI or cron without launching the Tauri window.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use lexai_core::{init_database, Term};
+
+#[derive(Parser)]
+#[command(name = "lexai-cli", about = "Headless companion to the LexAI desktop app")]
+struct Cli {
+    /// Path to lexai.db. Defaults to the same data directory the desktop
+    /// app uses (`~/.local/share/com.lexai.app/lexai.db` on Linux, etc.).
+    #[arg(long, global = true)]
+    db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Import terms from a CSV file in the same layout `export --format csv` produces.
+    Import {
+        /// Path to the CSV file to read.
+        path: PathBuf,
+    },
+    /// List every term in the database.
+    List,
+    /// Export terms to a CSV, Anki deck, or PDF file.
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Where to write the export.
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Run through due terms one at a time, marking each known/unknown.
+    Review {
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Anki,
+    Pdf,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let db_path = cli.db.unwrap_or_else(default_db_path);
+
+    match run(cli.command, &db_path).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(command: Command, db_path: &PathBuf) -> Result<(), String> {
+    let pool = init_database(db_path).await.map_err(|err| err.to_string())?;
+
+    match command {
+        Command::Import { path } => {
+            let terms = parse_import_csv(&path)?;
+            let count = terms.len();
+            for term in terms {
+                lexai_core::add_term(&pool, term.term, term.definition, term.definition_cn).await?;
+            }
+            println!("Imported {count} term(s) from {}", path.display());
+        }
+        Command::List => {
+            let terms = lexai_core::get_all_terms(&pool).await?;
+            for term in &terms {
+                println!("{}\t{}", term.term, term.definition);
+            }
+            println!("{} term(s)", terms.len());
+        }
+        Command::Export { format, output } => {
+            let terms = lexai_core::load_terms_sorted(&pool).await?;
+            if terms.is_empty() {
+                return Err("No terms available to export.".to_string());
+            }
+
+            match format {
+                ExportFormat::Csv => {
+                    let csv = lexai_core::build_csv(&terms)?;
+                    fs::write(&output, csv).map_err(|err| err.to_string())?;
+                }
+                ExportFormat::Anki => lexai_core::build_anki_package(&output, &terms)?,
+                ExportFormat::Pdf => lexai_core::build_pdf(&output, &terms)?,
+            }
+
+            println!("Exported {} term(s) to {}", terms.len(), output.display());
+        }
+        Command::Review { limit } => {
+            let due = lexai_core::get_review_terms(&pool, Some(limit)).await?;
+            if due.is_empty() {
+                println!("Nothing due for review.");
+                return Ok(());
+            }
+
+            for term in due {
+                println!("{}\n  {}", term.term, term.definition);
+                let known = prompt_known()?;
+                // The CLI only asks a simple yes/no, mapped onto SM-2's
+                // 0..=5 quality scale as a clean pass (5) or a clean lapse (2).
+                let quality = if known { 5 } else { 2 };
+                lexai_core::apply_review_result(&pool, term.id, quality).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt_known() -> Result<bool, String> {
+    use std::io::{self, Write};
+
+    loop {
+        print!("Did you know it? [y/n] ");
+        io::stdout().flush().map_err(|err| err.to_string())?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .read_line(&mut answer)
+            .map_err(|err| err.to_string())?;
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer y or n."),
+        }
+    }
+}
+
+/// Parses the CSV layout `lexai_core::build_csv` produces: a header row
+/// followed by `Term,Definition,Definition (zh-CN)` rows, every field
+/// double-quoted with internal quotes doubled.
+fn parse_import_csv(path: &PathBuf) -> Result<Vec<Term>, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut lines = contents.lines();
+    lines.next(); // header
+
+    let mut terms = Vec::new();
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let [term, definition, definition_cn] = parse_csv_row(line)?;
+        terms.push(Term {
+            id: 0,
+            term,
+            definition,
+            definition_cn: if definition_cn.is_empty() {
+                None
+            } else {
+                Some(definition_cn)
+            },
+            review_stage: 0,
+            last_reviewed_at: None,
+            ease_factor: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            next_review_at: None,
+        });
+    }
+
+    Ok(terms)
+}
+
+fn parse_csv_row(line: &str) -> Result<[String; 3], String> {
+    let mut fields = Vec::with_capacity(3);
+    let mut chars = line.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch == ',' {
+            chars.next();
+            continue;
+        }
+
+        let mut field = String::new();
+        if ch == '"' {
+            chars.next();
+            while let Some(ch) = chars.next() {
+                if ch == '"' {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        break;
+                    }
+                } else {
+                    field.push(ch);
+                }
+            }
+        } else {
+            while let Some(&ch) = chars.peek() {
+                if ch == ',' {
+                    break;
+                }
+                field.push(ch);
+                chars.next();
+            }
+        }
+        fields.push(field);
+    }
+
+    fields
+        .try_into()
+        .map_err(|fields: Vec<String>| format!("Expected 3 CSV fields, found {}", fields.len()))
+}
+
+fn default_db_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("com.lexai.app")
+        .join("lexai.db")
+}
@@ -0,0 +1,757 @@
+//! Shared database access, spaced-repetition scheduling, and export
+//! rendering for LexAI, factored out so both the Tauri desktop app and the
+//! headless `lexai-cli` binary can drive the same `lexai.db` without either
+//! one depending on the other.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{Datelike, Duration, TimeZone, Utc};
+use genpdf::{
+    elements::{Break, Paragraph, StyledElement},
+    fonts::{FontData, FontFamily},
+    style::Effect,
+    Document,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    ConnectOptions, Row, SqlitePool,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Term {
+    pub id: i64,
+    pub term: String,
+    pub definition: String,
+    pub definition_cn: Option<String>,
+    pub review_stage: i64,
+    pub last_reviewed_at: Option<String>,
+    /// SM-2 easiness factor, clamped to a floor of 1.3.
+    pub ease_factor: f64,
+    /// Current SM-2 interval, in days, used to derive `next_review_at`.
+    pub interval_days: i64,
+    /// Consecutive reviews with quality >= 3; reset to 0 on a lapse.
+    pub repetitions: i64,
+    /// Due date; a term is due for review once this has passed (or is unset).
+    pub next_review_at: Option<String>,
+}
+
+fn term_from_row(row: &sqlx::sqlite::SqliteRow) -> Term {
+    Term {
+        id: row.get("id"),
+        term: row.get("term"),
+        definition: row.get("definition"),
+        definition_cn: row.get("definition_cn"),
+        review_stage: row.get("review_stage"),
+        last_reviewed_at: row.get("last_reviewed_at"),
+        ease_factor: row.get("ease_factor"),
+        interval_days: row.get("interval_days"),
+        repetitions: row.get("repetitions"),
+        next_review_at: row.get("next_review_at"),
+    }
+}
+
+pub async fn init_database(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    let connect_options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true)
+        .disable_statement_logging();
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(connect_options)
+        .await?;
+
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+pub async fn add_term(
+    pool: &SqlitePool,
+    term: String,
+    definition: String,
+    definition_cn: Option<String>,
+) -> Result<(), String> {
+    sqlx::query("INSERT INTO terms (term, definition, definition_cn) VALUES (?, ?, ?)")
+        .bind(term)
+        .bind(definition)
+        .bind(definition_cn)
+        .execute(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+pub async fn get_all_terms(pool: &SqlitePool) -> Result<Vec<Term>, String> {
+    let records = sqlx::query(
+        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at, ease_factor, interval_days, repetitions, next_review_at FROM terms ORDER BY created_at DESC",
+    )
+        .fetch_all(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(records.iter().map(term_from_row).collect())
+}
+
+pub async fn find_term_by_name(pool: &SqlitePool, term: &str) -> Result<Option<Term>, String> {
+    let record = sqlx::query(
+        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at, ease_factor, interval_days, repetitions, next_review_at FROM terms WHERE lower(term) = lower(?) LIMIT 1",
+    )
+    .bind(term)
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| err.to_string())?;
+
+    Ok(record.as_ref().map(term_from_row))
+}
+
+pub async fn delete_term(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM terms WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+pub async fn update_term(
+    pool: &SqlitePool,
+    id: i64,
+    term: String,
+    definition: String,
+    definition_cn: Option<String>,
+) -> Result<(), String> {
+    sqlx::query("UPDATE terms SET term = ?, definition = ?, definition_cn = COALESCE(?, definition_cn) WHERE id = ?")
+        .bind(term)
+        .bind(definition)
+        .bind(definition_cn)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+pub async fn load_terms_sorted(pool: &SqlitePool) -> Result<Vec<Term>, String> {
+    let records = sqlx::query(
+        "SELECT id, term, COALESCE(definition, '') AS definition, COALESCE(definition_cn, '') AS definition_cn, review_stage, last_reviewed_at, ease_factor, interval_days, repetitions, next_review_at FROM terms ORDER BY lower(term) ASC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|err| err.to_string())?;
+
+    Ok(records
+        .into_iter()
+        .map(|row| Term {
+            id: row.get("id"),
+            term: row.get("term"),
+            definition: row.get("definition"),
+            definition_cn: {
+                let value: String = row.get("definition_cn");
+                if value.is_empty() {
+                    None
+                } else {
+                    Some(value)
+                }
+            },
+            review_stage: row.get("review_stage"),
+            last_reviewed_at: row.get("last_reviewed_at"),
+            ease_factor: row.get("ease_factor"),
+            interval_days: row.get("interval_days"),
+            repetitions: row.get("repetitions"),
+            next_review_at: row.get("next_review_at"),
+        })
+        .collect())
+}
+
+/// Selects terms due for review: never reviewed yet (`next_review_at` is
+/// unset) or whose `next_review_at` has already passed, oldest-due first.
+pub async fn get_review_terms(pool: &SqlitePool, limit: Option<i64>) -> Result<Vec<Term>, String> {
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    let now = Utc::now().to_rfc3339();
+
+    let records = sqlx::query(
+        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at, ease_factor, interval_days, repetitions, next_review_at FROM terms WHERE next_review_at IS NULL OR next_review_at <= ? ORDER BY COALESCE(next_review_at, '') ASC, created_at ASC LIMIT ?",
+    )
+    .bind(&now)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|err| err.to_string())?;
+
+    Ok(records.iter().map(term_from_row).collect())
+}
+
+/// Applies one SM-2 review step for `quality` (0..=5, where 0 is a total
+/// blank and 5 is a perfect recall): a pass (`quality >= 3`) advances the
+/// interval (1 day, then 6 days, then `interval * ease_factor` thereafter)
+/// and the repetition streak; a lapse resets both to their first-review
+/// values. `ease_factor` is nudged by the standard SM-2 formula and floored
+/// at 1.3 regardless of outcome. `review_stage` is kept in step with
+/// `repetitions` so the S3 sync "pick the more-reviewed copy" merge logic
+/// still works unchanged.
+pub async fn apply_review_result(pool: &SqlitePool, id: i64, quality: i64) -> Result<(), String> {
+    let quality = quality.clamp(0, 5);
+
+    let record = sqlx::query("SELECT ease_factor, interval_days, repetitions FROM terms WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let Some(row) = record else {
+        return Err("Term not found".to_string());
+    };
+
+    let ease_factor: f64 = row.get("ease_factor");
+    let interval_days: i64 = row.get("interval_days");
+    let repetitions: i64 = row.get("repetitions");
+
+    let (next_repetitions, next_interval) = if quality >= 3 {
+        let interval = match repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (interval_days as f64 * ease_factor).round() as i64,
+        };
+        (repetitions + 1, interval)
+    } else {
+        (0, 1)
+    };
+
+    let quality = quality as f64;
+    let next_ease_factor =
+        (ease_factor + (0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02))).max(1.3);
+
+    let now = Utc::now();
+    let last_reviewed_at = now.to_rfc3339();
+    let next_review_at = (now + Duration::days(next_interval)).to_rfc3339();
+
+    sqlx::query(
+        "UPDATE terms SET review_stage = ?, ease_factor = ?, interval_days = ?, repetitions = ?, next_review_at = ?, last_reviewed_at = ? WHERE id = ?",
+    )
+    .bind(next_repetitions)
+    .bind(next_ease_factor)
+    .bind(next_interval)
+    .bind(next_repetitions)
+    .bind(next_review_at)
+    .bind(last_reviewed_at)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+fn escape_csv_cell(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for ch in value.chars() {
+        if ch == '"' {
+            escaped.push('"');
+            escaped.push('"');
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+pub fn build_csv(terms: &[Term]) -> Result<String, String> {
+    let mut csv = String::from("Term,Definition,Definition (zh-CN)\n");
+    for entry in terms {
+        let line = format!(
+            "{},{},{}\n",
+            escape_csv_cell(&entry.term),
+            escape_csv_cell(&entry.definition),
+            escape_csv_cell(entry.definition_cn.as_deref().unwrap_or(""))
+        );
+        csv.push_str(&line);
+    }
+    Ok(csv)
+}
+
+fn build_anki_back_field(definition: &str, definition_cn: Option<&str>) -> String {
+    let mut content = encode_html(definition);
+    content = content.replace('\n', "<br>");
+
+    if let Some(def_cn) = definition_cn {
+        if !def_cn.trim().is_empty() {
+            let mut cn = encode_html(def_cn);
+            cn = cn.replace('\n', "<br>");
+            content.push_str("<br><div class=\"definition-cn\">");
+            content.push_str(&cn);
+            content.push_str("</div>");
+        }
+    }
+
+    content
+}
+
+fn encode_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+// Fixed so re-exporting the same collection always produces the same model
+// and deck identity; only note/card ids (derived from the export time) and
+// the `mod`/`crt` timestamps change between runs.
+const ANKI_MODEL_ID: i64 = 1_700_000_000_001;
+const ANKI_DECK_ID: i64 = 1_700_000_000_002;
+const ANKI_DCONF_ID: i64 = 1;
+/// Separates field values within a note's `flds` column, per the Anki
+/// collection format.
+const ANKI_FIELD_SEPARATOR: char = '\u{1f}';
+
+fn sha1_hex(value: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(value.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// A stable note id derived from the term text rather than the row id, so
+/// re-exporting (or exporting from a different device after a sync) the
+/// same term produces the same guid and Anki can recognize it as the same
+/// note instead of creating a duplicate.
+fn stable_guid(term: &str) -> String {
+    sha1_hex(term)[..10].to_string()
+}
+
+/// Anki's `csum`: the first 8 hex digits of the first field's SHA1, read as
+/// an integer. Used to detect duplicate notes on import.
+fn first_field_checksum(first_field: &str) -> i64 {
+    i64::from_str_radix(&sha1_hex(first_field)[..8], 16).unwrap_or(0)
+}
+
+/// Builds the `collection.anki2` SQLite database: the standard `col`,
+/// `notes`, `cards`, and `revlog` tables (plus `graves`, which Anki expects
+/// to exist even when empty), one deterministic `Model`/`Deck` pair, and one
+/// note+card per term. Cards seed their scheduling (`ivl`/`factor`/`due`)
+/// from the term's SM-2 columns so review history survives the export
+/// instead of every card looking brand new.
+fn build_anki_collection(collection_path: &Path, terms: &[Term]) -> Result<(), String> {
+    let conn = Connection::open(collection_path).map_err(|err| err.to_string())?;
+    conn.execute_batch(
+        "
+        CREATE TABLE col (
+            id integer primary key,
+            crt integer not null,
+            mod integer not null,
+            scm integer not null,
+            ver integer not null,
+            dty integer not null,
+            usn integer not null,
+            ls integer not null,
+            conf text not null,
+            models text not null,
+            decks text not null,
+            dconf text not null,
+            tags text not null
+        );
+        CREATE TABLE notes (
+            id integer primary key,
+            guid text not null,
+            mid integer not null,
+            mod integer not null,
+            usn integer not null,
+            tags text not null,
+            flds text not null,
+            sfld text not null,
+            csum integer not null,
+            flags integer not null,
+            data text not null
+        );
+        CREATE TABLE cards (
+            id integer primary key,
+            nid integer not null,
+            did integer not null,
+            ord integer not null,
+            mod integer not null,
+            usn integer not null,
+            type integer not null,
+            queue integer not null,
+            due integer not null,
+            ivl integer not null,
+            factor integer not null,
+            reps integer not null,
+            lapses integer not null,
+            left integer not null,
+            odue integer not null,
+            odid integer not null,
+            flags integer not null,
+            data text not null
+        );
+        CREATE TABLE revlog (
+            id integer primary key,
+            cid integer not null,
+            usn integer not null,
+            ease integer not null,
+            ivl integer not null,
+            lastIvl integer not null,
+            factor integer not null,
+            time integer not null,
+            type integer not null
+        );
+        CREATE TABLE graves (
+            usn integer not null,
+            oid integer not null,
+            type integer not null
+        );
+        CREATE INDEX ix_notes_usn ON notes (usn);
+        CREATE INDEX ix_cards_usn ON cards (usn);
+        CREATE INDEX ix_revlog_usn ON revlog (usn);
+        CREATE INDEX ix_cards_nid ON cards (nid);
+        CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+        CREATE INDEX ix_revlog_cid ON revlog (cid);
+        CREATE INDEX ix_notes_csum ON notes (csum);
+        ",
+    )
+    .map_err(|err| err.to_string())?;
+
+    let now = Utc::now();
+    let now_secs = now.timestamp();
+    let crt_secs = Utc
+        .with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+        .timestamp();
+
+    let model = json!({
+        (ANKI_MODEL_ID.to_string()): {
+            "id": ANKI_MODEL_ID,
+            "name": "LexAI Basic",
+            "type": 0,
+            "mod": now_secs,
+            "usn": -1,
+            "sortf": 0,
+            "did": ANKI_DECK_ID,
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Front}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Back}}",
+                "bqfmt": "",
+                "bafmt": "",
+                "did": null,
+            }],
+            "flds": [
+                {"name": "Front", "ord": 0, "sticky": false, "rtl": false, "font": "Arial", "size": 20},
+                {"name": "Back", "ord": 1, "sticky": false, "rtl": false, "font": "Arial", "size": 20},
+            ],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }",
+            "latexPre": "\\documentclass[12pt]{article}\\special{papersize=3in,5in}\\usepackage[utf8]{inputenc}\\usepackage{amssymb,amsmath}\\pagestyle{empty}\\setlength{\\parindent}{0in}\\begin{document}",
+            "latexPost": "\\end{document}",
+            "req": [[0, "any", [0]]],
+        }
+    });
+
+    let deck = json!({
+        (ANKI_DECK_ID.to_string()): {
+            "id": ANKI_DECK_ID,
+            "name": "LexAI Termbase",
+            "mod": now_secs,
+            "usn": -1,
+            "collapsed": false,
+            "desc": "Exported from LexAI",
+            "dyn": 0,
+            "conf": ANKI_DCONF_ID,
+            "extendNew": 10,
+            "extendRev": 50,
+        }
+    });
+
+    let dconf = json!({
+        (ANKI_DCONF_ID.to_string()): {
+            "id": ANKI_DCONF_ID,
+            "name": "Default",
+            "new": {"perDay": 20, "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "bury": false},
+            "rev": {"perDay": 200, "ease4": 1.3, "fuzz": 0.05, "minSpace": 1, "ivlFct": 1, "maxIvl": 36500, "bury": false},
+            "lapse": {"delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0},
+            "maxTaken": 60,
+            "timer": 0,
+            "autoplay": true,
+            "replayq": true,
+            "mod": 0,
+            "usn": 0,
+        }
+    });
+
+    let conf = json!({
+        "nextPos": terms.len() + 1,
+        "estTimes": true,
+        "activeDecks": [ANKI_DECK_ID],
+        "sortType": "noteFld",
+        "timeLim": 0,
+        "sortBackwards": false,
+        "addToCur": true,
+        "curDeck": ANKI_DECK_ID,
+        "newBury": true,
+        "newSpread": 0,
+        "dueCounts": true,
+        "curModel": ANKI_MODEL_ID.to_string(),
+        "collapseTime": 1200,
+    });
+
+    conn.execute(
+        "INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags) VALUES (1, ?, ?, ?, 11, 0, 0, 0, ?, ?, ?, ?, '{}')",
+        rusqlite::params![
+            crt_secs,
+            now_secs,
+            now_secs,
+            conf.to_string(),
+            model.to_string(),
+            deck.to_string(),
+            dconf.to_string(),
+        ],
+    )
+    .map_err(|err| err.to_string())?;
+
+    for (index, term) in terms.iter().enumerate() {
+        let note_id = now_secs * 1000 + (index as i64) * 2;
+        let card_id = note_id + 1;
+        let back = build_anki_back_field(&term.definition, term.definition_cn.as_deref());
+        let flds = format!("{}{ANKI_FIELD_SEPARATOR}{back}", term.term);
+
+        conn.execute(
+            "INSERT INTO notes (id, guid, mid, mod, usn, tags, flds, sfld, csum, flags, data) VALUES (?, ?, ?, ?, -1, '', ?, ?, ?, 0, '')",
+            rusqlite::params![
+                note_id,
+                stable_guid(&term.term),
+                ANKI_MODEL_ID,
+                now_secs,
+                flds,
+                term.term,
+                first_field_checksum(&term.term),
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+
+        // A term that's never been reviewed is a fresh "new" card; one
+        // that's been through at least one SM-2 review is a "review" card
+        // due on the day its schedule says, so its history carries over.
+        let (queue, card_type, due, ivl, factor) = if term.repetitions > 0 {
+            let next_review_secs = term
+                .next_review_at
+                .as_deref()
+                .and_then(|value| chrono::DateTime::parse_from_rfc3339(value).ok())
+                .map(|value| value.timestamp())
+                .unwrap_or(now_secs);
+            let due_day = ((next_review_secs - crt_secs) as f64 / 86_400.0).floor() as i64;
+            (
+                2,
+                2,
+                due_day,
+                term.interval_days.max(1),
+                (term.ease_factor * 1000.0).round() as i64,
+            )
+        } else {
+            (0, 0, index as i64 + 1, 0, 0)
+        };
+
+        conn.execute(
+            "INSERT INTO cards (id, nid, did, ord, mod, usn, type, queue, due, ivl, factor, reps, lapses, left, odue, odid, flags, data) VALUES (?, ?, ?, 0, ?, -1, ?, ?, ?, ?, ?, ?, 0, 0, 0, 0, 0, '')",
+            rusqlite::params![
+                card_id,
+                note_id,
+                ANKI_DECK_ID,
+                now_secs,
+                card_type,
+                queue,
+                due,
+                ivl,
+                factor,
+                term.repetitions,
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Exports `terms` as a real `.apkg`: a ZIP containing the SQLite
+/// `collection.anki2` built by [`build_anki_collection`] and an empty
+/// `media` map, so the result can be double-clicked straight into Anki
+/// instead of requiring the user to paste HTML by hand.
+pub fn build_anki_package(path: &Path, terms: &[Term]) -> Result<(), String> {
+    let collection_path =
+        std::env::temp_dir().join(format!("lexai-anki-{}.anki2", std::process::id()));
+
+    let result = build_anki_collection(&collection_path, terms)
+        .and_then(|()| std::fs::read(&collection_path).map_err(|err| err.to_string()));
+    // Always clear the temp collection file, success or failure: it's keyed
+    // only by pid, so a stale partial file left behind by a failed build
+    // would make every subsequent export in this process fail immediately
+    // with a "table already exists" error until restart.
+    let _ = std::fs::remove_file(&collection_path);
+    let collection_bytes = result?;
+
+    let file = File::create(path).map_err(|err| err.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let file_options =
+        || SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("collection.anki2", file_options())
+        .map_err(|err| err.to_string())?;
+    zip.write_all(&collection_bytes)
+        .map_err(|err| err.to_string())?;
+
+    zip.start_file("media", file_options())
+        .map_err(|err| err.to_string())?;
+    zip.write_all(b"{}").map_err(|err| err.to_string())?;
+
+    zip.finish().map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn load_pdf_font_family() -> Result<FontFamily<FontData>, String> {
+    let font_bytes = include_bytes!("../resources/fonts/DejaVuSans.ttf");
+    let load = |data: &[u8]| {
+        FontData::new(data.to_vec(), None).map_err(|err| format!("Failed to load font: {err}"))
+    };
+
+    Ok(FontFamily {
+        regular: load(font_bytes)?,
+        bold: load(font_bytes)?,
+        italic: load(font_bytes)?,
+        bold_italic: load(font_bytes)?,
+    })
+}
+
+fn sanitize_pdf_text(value: &str) -> String {
+    value
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\t', "    ")
+}
+
+pub fn build_pdf(path: &Path, terms: &[Term]) -> Result<(), String> {
+    let font_family = load_pdf_font_family()?;
+
+    let mut doc = Document::new(font_family);
+    doc.set_title("LexAI Terminology Export");
+    doc.set_minimal_conformance();
+
+    for term in terms {
+        let heading = StyledElement::new(Paragraph::new(term.term.clone()), Effect::Bold);
+        doc.push(heading);
+
+        doc.push(Paragraph::new(sanitize_pdf_text(&term.definition)));
+
+        if let Some(def_cn) = term.definition_cn.as_deref() {
+            if !def_cn.is_empty() {
+                doc.push(Paragraph::new(sanitize_pdf_text(def_cn)));
+            }
+        }
+
+        doc.push(Break::new(1.2));
+    }
+
+    doc.render_to_file(path)
+        .map_err(|err| format!("Failed to write PDF: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    async fn setup_pool() -> (tempfile::TempDir, SqlitePool) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("lexai.db");
+        let pool = init_database(&db_path).await.unwrap();
+        (dir, pool)
+    }
+
+    #[tokio::test]
+    async fn apply_review_result_advances_and_regresses_stage() {
+        let (_dir, pool) = setup_pool().await;
+
+        add_term(
+            &pool,
+            "Neural Network".to_string(),
+            "An interconnected group of nodes.".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let term = find_term_by_name(&pool, "Neural Network")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(term.ease_factor, 2.5);
+
+        apply_review_result(&pool, term.id, 5).await.unwrap();
+        let after_first_pass = find_term_by_name(&pool, "Neural Network")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after_first_pass.repetitions, 1);
+        assert_eq!(after_first_pass.review_stage, 1);
+        assert_eq!(after_first_pass.interval_days, 1);
+        assert!(after_first_pass.last_reviewed_at.is_some());
+        assert!(after_first_pass.next_review_at.is_some());
+
+        apply_review_result(&pool, term.id, 5).await.unwrap();
+        let after_second_pass = find_term_by_name(&pool, "Neural Network")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after_second_pass.repetitions, 2);
+        assert_eq!(after_second_pass.interval_days, 6);
+
+        apply_review_result(&pool, term.id, 1).await.unwrap();
+        let after_lapse = find_term_by_name(&pool, "Neural Network")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after_lapse.repetitions, 0);
+        assert_eq!(after_lapse.review_stage, 0);
+        assert_eq!(after_lapse.interval_days, 1);
+    }
+
+    #[test]
+    fn build_csv_escapes_commas_and_quotes() {
+        let terms = vec![Term {
+            id: 1,
+            term: "A, B".to_string(),
+            definition: "Has \"quotes\"".to_string(),
+            definition_cn: None,
+            review_stage: 0,
+            last_reviewed_at: None,
+            ease_factor: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            next_review_at: None,
+        }];
+
+        let csv = build_csv(&terms).unwrap();
+        assert!(csv.contains("\"A, B\""));
+        assert!(csv.contains("\"Has \"\"quotes\"\"\""));
+    }
+}
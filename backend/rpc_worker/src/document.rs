@@ -1,27 +1,56 @@
+use crate::tokenizer::TokenizerService;
 use anyhow::{anyhow, Context, Result};
 use pdf_extract::extract_text;
 use std::path::Path;
 
-const CHUNK_SIZE: usize = 1000;
-const CHUNK_OVERLAP: usize = 200;
+/// Ordered separators tried from coarsest to finest; the empty string is the
+/// final fallback that splits by character when nothing else fits the budget.
+const SEPARATORS: [&str; 5] = ["\n\n", "\n", ". ", " ", ""];
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkConfig {
+    pub chunk_size: usize,
+    pub chunk_overlap: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        // Sized well under the embedding model's 512-token window so a chunk
+        // plus its overlap never risks truncation downstream.
+        Self {
+            chunk_size: 256,
+            chunk_overlap: 32,
+        }
+    }
+}
 
 pub struct ProcessedDocument {
     pub text: String,
     pub chunks: Vec<String>,
 }
 
-pub fn process_pdf(path: &Path) -> Result<ProcessedDocument> {
+pub fn process_pdf(
+    path: &Path,
+    tokenizer: &TokenizerService,
+    config: &ChunkConfig,
+) -> Result<ProcessedDocument> {
     if !path.exists() {
         return Err(anyhow!("file not found: {}", path.display()));
     }
 
-    let raw_text = extract_text(path).context("failed to extract pdf text")?;
+    let raw_text = match extract_text(path) {
+        Ok(text) => text,
+        Err(err) if err.to_string().to_lowercase().contains("encrypted") => {
+            return Err(anyhow!("pdf is encrypted and cannot be parsed"));
+        }
+        Err(err) => return Err(err).context("failed to extract pdf text"),
+    };
     let trimmed = raw_text.trim().to_owned();
     if trimmed.is_empty() {
         return Err(anyhow!("extracted text is empty"));
     }
 
-    let chunks = split_into_chunks(&trimmed);
+    let chunks = split_into_chunks(&trimmed, tokenizer, config)?;
     if chunks.is_empty() {
         return Err(anyhow!("no text chunks generated"));
     }
@@ -32,33 +61,161 @@ pub fn process_pdf(path: &Path) -> Result<ProcessedDocument> {
     })
 }
 
-fn split_into_chunks(text: &str) -> Vec<String> {
+fn split_into_chunks(
+    text: &str,
+    tokenizer: &TokenizerService,
+    config: &ChunkConfig,
+) -> Result<Vec<String>> {
     if text.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
-    let chars: Vec<char> = text.chars().collect();
-    let mut start = 0usize;
-    let len = chars.len();
-    let mut results = Vec::new();
+    let pieces = recursive_split(text, tokenizer, config, 0)?;
+    let merged = merge_with_overlap(pieces, tokenizer, config)?;
+    explode_oversized_chunks(merged, tokenizer)
+}
 
-    while start < len {
-        let end = (start + CHUNK_SIZE).min(len);
-        let chunk: String = chars[start..end].iter().collect();
-        results.push(chunk);
+/// `merge_with_overlap` targets `config.chunk_size`, but a misconfigured
+/// `ChunkConfig` (or pathological input with no separators at all) can still
+/// hand `encode` a chunk longer than the model's context window, which would
+/// silently truncate it. Re-slice any such chunk into overlapping token
+/// windows via `TokenizerService::chunk_text` so nothing past the window is
+/// lost; well-formed chunks pass through untouched.
+fn explode_oversized_chunks(
+    chunks: Vec<String>,
+    tokenizer: &TokenizerService,
+) -> Result<Vec<String>> {
+    let window = tokenizer.max_sequence_length();
+    let overlap = window / 8;
+    let mut exploded = Vec::with_capacity(chunks.len());
 
-        if end == len {
-            break;
+    for chunk in chunks {
+        if tokenizer.count_tokens(&chunk)? <= window {
+            exploded.push(chunk);
+        } else {
+            exploded.extend(tokenizer.chunk_text(&chunk, window, overlap)?);
         }
+    }
+
+    Ok(exploded)
+}
 
-        start = if CHUNK_OVERLAP >= CHUNK_SIZE {
-            end
+/// Splits `text` on the first separator in `SEPARATORS[separator_idx..]` that
+/// fits, recursing into any piece still over budget with the next separator.
+fn recursive_split(
+    text: &str,
+    tokenizer: &TokenizerService,
+    config: &ChunkConfig,
+    separator_idx: usize,
+) -> Result<Vec<String>> {
+    if tokenizer.count_tokens(text)? <= config.chunk_size {
+        return Ok(vec![text.to_string()]);
+    }
+
+    let separator = SEPARATORS[separator_idx];
+    if separator.is_empty() {
+        return split_by_chars(text, tokenizer, config);
+    }
+
+    let parts: Vec<&str> = text.split(separator).collect();
+    let mut pieces = Vec::new();
+    for (idx, part) in parts.iter().enumerate() {
+        let piece = if idx + 1 < parts.len() {
+            format!("{part}{separator}")
         } else {
-            end.saturating_sub(CHUNK_OVERLAP)
+            (*part).to_string()
         };
+        if piece.is_empty() {
+            continue;
+        }
+
+        if tokenizer.count_tokens(&piece)? <= config.chunk_size {
+            pieces.push(piece);
+        } else {
+            pieces.extend(recursive_split(&piece, tokenizer, config, separator_idx + 1)?);
+        }
+    }
+    Ok(pieces)
+}
+
+/// Final fallback once no separator helps: halve the text by character until
+/// every half fits the token budget.
+fn split_by_chars(text: &str, tokenizer: &TokenizerService, config: &ChunkConfig) -> Result<Vec<String>> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= 1 {
+        return Ok(vec![text.to_string()]);
+    }
+
+    let mid = chars.len() / 2;
+    let (left, right) = chars.split_at(mid);
+    let mut pieces = Vec::new();
+    for half in [left.iter().collect::<String>(), right.iter().collect::<String>()] {
+        if tokenizer.count_tokens(&half)? <= config.chunk_size {
+            pieces.push(half);
+        } else {
+            pieces.extend(split_by_chars(&half, tokenizer, config)?);
+        }
+    }
+    Ok(pieces)
+}
+
+/// Greedily merges adjacent small pieces up to the token budget, carrying the
+/// trailing pieces of the previous chunk forward so overlap lands on natural
+/// separator boundaries rather than mid-word.
+fn merge_with_overlap(
+    pieces: Vec<String>,
+    tokenizer: &TokenizerService,
+    config: &ChunkConfig,
+) -> Result<Vec<String>> {
+    let mut chunks = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut current_tokens = 0usize;
+
+    for piece in pieces {
+        let piece_tokens = tokenizer.count_tokens(&piece)?;
+        if !current.is_empty() && current_tokens + piece_tokens > config.chunk_size {
+            chunks.push(current.concat());
+            current = carry_overlap(&current, tokenizer, config)?;
+            current_tokens = token_total(&current, tokenizer)?;
+        }
+        current_tokens += piece_tokens;
+        current.push(piece);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current.concat());
+    }
+
+    Ok(chunks)
+}
+
+fn carry_overlap(
+    current: &[String],
+    tokenizer: &TokenizerService,
+    config: &ChunkConfig,
+) -> Result<Vec<String>> {
+    let mut carried = Vec::new();
+    let mut tokens = 0usize;
+
+    for piece in current.iter().rev() {
+        let piece_tokens = tokenizer.count_tokens(piece)?;
+        if tokens + piece_tokens > config.chunk_overlap {
+            break;
+        }
+        tokens += piece_tokens;
+        carried.push(piece.clone());
     }
 
-    results
+    carried.reverse();
+    Ok(carried)
+}
+
+fn token_total(pieces: &[String], tokenizer: &TokenizerService) -> Result<usize> {
+    let mut total = 0usize;
+    for piece in pieces {
+        total += tokenizer.count_tokens(piece)?;
+    }
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -71,45 +228,53 @@ mod tests {
 
     #[test]
     fn short_text_yields_single_chunk() {
+        let tokenizer = TokenizerService::new().expect("model assets required for this test");
+        let config = ChunkConfig::default();
         let text = "hello lexai";
-        let chunks = split_into_chunks(text);
+        let chunks = split_into_chunks(text, &tokenizer, &config).unwrap();
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0], text);
     }
 
     #[test]
-    fn long_text_generates_expected_chunk_count() {
-        let text = repeat("a", 2_500);
-        let chunks = split_into_chunks(&text);
-        assert_eq!(chunks.len(), 3);
-        let total: usize = chunks.iter().map(|chunk| chunk.len()).sum();
-        // Overlaps cause repeated characters, but total length should be original length + overlaps
-        assert!(total >= text.len());
-    }
-
-    #[test]
-    fn overlapping_regions_are_preserved() {
-        let mut text = repeat("x", 1_000);
-        text.push_str(&repeat("y", 800));
-        text.push_str(&repeat("z", 800));
-        let chunks = split_into_chunks(&text);
-        assert!(chunks.len() >= 3);
-        for window in chunks.windows(2) {
-            let first = &window[0];
-            let second = &window[1];
-            let overlap = CHUNK_OVERLAP.min(first.len()).min(second.len());
-            let first_tail = &first[first.len() - overlap..];
-            let second_head = &second[..overlap];
-            assert_eq!(first_tail, second_head);
+    fn long_text_is_split_within_token_budget() {
+        let tokenizer = TokenizerService::new().expect("model assets required for this test");
+        let config = ChunkConfig {
+            chunk_size: 8,
+            chunk_overlap: 2,
+        };
+        let mut text = String::new();
+        for i in 0..40 {
+            text.push_str(&format!("Sentence number {i} of the document. "));
+        }
+        let chunks = split_into_chunks(&text, &tokenizer, &config).unwrap();
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(tokenizer.count_tokens(chunk).unwrap() <= config.chunk_size + config.chunk_overlap);
         }
     }
 
     #[test]
     fn empty_or_whitespace_text_handled() {
-        assert!(split_into_chunks("").is_empty());
+        let tokenizer = TokenizerService::new().expect("model assets required for this test");
+        let config = ChunkConfig::default();
+        assert!(split_into_chunks("", &tokenizer, &config).unwrap().is_empty());
         let whitespace = "   \n\t";
-        let chunks = split_into_chunks(whitespace);
+        let chunks = split_into_chunks(whitespace, &tokenizer, &config).unwrap();
         assert_eq!(chunks.len(), 1);
         assert_eq!(chunks[0], whitespace);
     }
+
+    #[test]
+    fn repeated_long_run_eventually_falls_back_to_char_split() {
+        let tokenizer = TokenizerService::new().expect("model assets required for this test");
+        let config = ChunkConfig {
+            chunk_size: 8,
+            chunk_overlap: 0,
+        };
+        let text = repeat("a,", 1_000);
+        let chunks = split_into_chunks(&text, &tokenizer, &config).unwrap();
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat().len(), text.len());
+    }
 }
@@ -0,0 +1,146 @@
+use crate::embeddings::EmbeddingEngine;
+use anyhow::{Context, Result};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Entries to keep in memory before evicting the least-recently-used one.
+/// Sized for a few thousand documents' worth of chunks without unbounded
+/// growth across long-running re-indexing sessions.
+const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    hash: String,
+    vector: Vec<f32>,
+    /// `EmbeddingEngine::model_id` of whichever engine produced `vector`.
+    /// Entries from a different model than the one currently configured are
+    /// dropped on load instead of served as stale cross-model vectors.
+    model: String,
+}
+
+/// Wraps an `EmbeddingEngine` with a content-hash keyed LRU cache, so
+/// re-embedding a chunk whose text hasn't changed between indexing passes
+/// is a hash lookup instead of an ONNX run. The hash (blake3, matching the
+/// chunk-hashing already used for incremental re-indexing in `jsonrpc.rs`)
+/// is computed over the raw input text, independent of tokenization.
+pub struct CachedEmbeddingEngine {
+    inner: Arc<dyn EmbeddingEngine>,
+    cache: Mutex<LruCache<String, Vec<f32>>>,
+    persist_path: Option<PathBuf>,
+}
+
+impl CachedEmbeddingEngine {
+    pub fn new(inner: Arc<dyn EmbeddingEngine>) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(inner: Arc<dyn EmbeddingEngine>, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(capacity.max(1)).expect("capacity clamped to at least 1"),
+            )),
+            persist_path: None,
+        }
+    }
+
+    /// Loads any entries previously written by `persist()` at `path` into
+    /// the cache, and remembers `path` so later `persist()` calls write
+    /// back there. A missing or unreadable file is treated as an empty
+    /// cache rather than an error, since a cold start is always valid.
+    /// Entries persisted under a different `model_id` than `self.inner`'s
+    /// are skipped, so switching embedding providers never serves a vector
+    /// the new model wouldn't have produced.
+    pub fn with_disk_persistence(mut self, path: PathBuf) -> Self {
+        if let Ok(bytes) = std::fs::read(&path) {
+            if let Ok(entries) = serde_json::from_slice::<Vec<PersistedEntry>>(&bytes) {
+                let model_id = self.inner.model_id().to_string();
+                let mut cache = self.cache.lock().expect("embedding cache mutex poisoned");
+                for entry in entries {
+                    if entry.model == model_id {
+                        cache.put(entry.hash, entry.vector);
+                    }
+                }
+            }
+        }
+        self.persist_path = Some(path);
+        self
+    }
+
+    /// Writes the current cache contents to the path passed to
+    /// `with_disk_persistence`, if any. A no-op otherwise.
+    pub fn persist(&self) -> Result<()> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+
+        let model_id = self.inner.model_id().to_string();
+        let entries: Vec<PersistedEntry> = {
+            let cache = self.cache.lock().expect("embedding cache mutex poisoned");
+            cache
+                .iter()
+                .map(|(hash, vector)| PersistedEntry {
+                    hash: hash.clone(),
+                    vector: vector.clone(),
+                    model: model_id.clone(),
+                })
+                .collect()
+        };
+
+        let bytes =
+            serde_json::to_vec(&entries).context("failed to serialize embedding cache")?;
+        std::fs::write(path, bytes).context("failed to write embedding cache to disk")?;
+        Ok(())
+    }
+
+    fn content_hash(text: &str) -> String {
+        blake3::hash(text.as_bytes()).to_hex().to_string()
+    }
+}
+
+impl EmbeddingEngine for CachedEmbeddingEngine {
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let hashes: Vec<String> = texts.iter().map(|text| Self::content_hash(text)).collect();
+        let mut results: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+        let mut miss_indices = Vec::new();
+
+        {
+            let mut cache = self.cache.lock().expect("embedding cache mutex poisoned");
+            for (idx, hash) in hashes.iter().enumerate() {
+                if let Some(vector) = cache.get(hash) {
+                    results[idx] = Some(vector.clone());
+                } else {
+                    miss_indices.push(idx);
+                }
+            }
+        }
+
+        if !miss_indices.is_empty() {
+            let miss_texts: Vec<String> =
+                miss_indices.iter().map(|&idx| texts[idx].clone()).collect();
+            let miss_vectors = self.inner.embed(&miss_texts)?;
+
+            let mut cache = self.cache.lock().expect("embedding cache mutex poisoned");
+            for (&idx, vector) in miss_indices.iter().zip(miss_vectors) {
+                cache.put(hashes[idx].clone(), vector.clone());
+                results[idx] = Some(vector);
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|vector| vector.expect("every index is filled by a cache hit or a miss lookup"))
+            .collect())
+    }
+
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::retry::embed_with_retry;
 use crate::tokenizer::TokenizerService;
 use anyhow::{Context, Result};
 use ndarray::{s, Array2, ArrayD, CowArray, IxDyn};
@@ -5,23 +6,57 @@ use once_cell::sync::OnceCell;
 use ort::{
     environment::Environment, session::SessionBuilder, value::Value, GraphOptimizationLevel,
 };
+use rayon::prelude::*;
 use std::sync::Arc;
 
 const MODEL_ONNX_PATH: &str = "onnx/model.onnx";
 
+/// Cache-key discriminator for vectors produced by this engine; see
+/// `EmbeddingEngine::model_id`.
+const MODEL_ID: &str = "local-onnx:all-MiniLM-L6-v2";
+
+/// Default width of the rayon pool used by `embed_parallel` for batch
+/// embedding, keeping concurrent ONNX sessions within a controllable bound.
+pub const REQUEST_PARALLELISM: usize = 4;
+
+/// Default token budget for a single ONNX forward pass (`batch_len *
+/// sequence_length`), sized to keep worst-case memory use predictable on a
+/// single CPU core.
+pub const DEFAULT_MAX_TOKENS: usize = 4096;
+
 pub trait EmbeddingEngine: Send + Sync {
     fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Identifies which model/provider produced this engine's vectors.
+    /// `CachedEmbeddingEngine` keys its persisted cache by this alongside
+    /// the content hash, so switching providers never serves a vector the
+    /// new model wouldn't have produced.
+    fn model_id(&self) -> &str;
 }
 
 pub struct EmbeddingService {
     tokenizer: Arc<TokenizerService>,
     session: ort::Session,
+    max_tokens: usize,
+    max_batch_len: Option<usize>,
 }
 
 static ORT_ENV: OnceCell<Arc<Environment>> = OnceCell::new();
 
 impl EmbeddingService {
     pub fn new(tokenizer: Arc<TokenizerService>) -> Result<Self> {
+        Self::with_token_budget(tokenizer, DEFAULT_MAX_TOKENS, None)
+    }
+
+    /// Like `new`, but lets the caller bound micro-batch sizing explicitly:
+    /// `max_tokens` caps `batch_len * sequence_length` for a single ONNX
+    /// forward pass, and `max_batch_len` additionally caps the number of
+    /// texts per micro-batch regardless of how short they are.
+    pub fn with_token_budget(
+        tokenizer: Arc<TokenizerService>,
+        max_tokens: usize,
+        max_batch_len: Option<usize>,
+    ) -> Result<Self> {
         let env = ORT_ENV
             .get_or_try_init(|| {
                 Environment::builder()
@@ -40,10 +75,79 @@ impl EmbeddingService {
             .with_model_from_file(TokenizerService::model_path(MODEL_ONNX_PATH)?)
             .context("failed to load ONNX model")?;
 
-        Ok(Self { tokenizer, session })
+        Ok(Self {
+            tokenizer,
+            session,
+            max_tokens,
+            max_batch_len,
+        })
     }
 
     pub fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let micro_batches = self.plan_micro_batches(texts)?;
+        let mut results: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+
+        for indices in micro_batches {
+            let batch_texts: Vec<String> = indices.iter().map(|&i| texts[i].clone()).collect();
+            let vectors = self.embed_batch(&batch_texts)?;
+            for (&original_idx, vector) in indices.iter().zip(vectors) {
+                results[original_idx] = Some(vector);
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|v| v.expect("every input index is assigned exactly one micro-batch"))
+            .collect())
+    }
+
+    /// Bins `texts` into micro-batches bounded by `max_tokens` (approximated
+    /// as `batch_len * sequence_length`, since padding grows with the
+    /// longest member of the batch) and, optionally, `max_batch_len`. Texts
+    /// are sorted by token count first so similarly-sized texts land in the
+    /// same micro-batch, minimizing padding waste; the original input order
+    /// is restored by `embed` via the returned indices.
+    fn plan_micro_batches(&self, texts: &[String]) -> Result<Vec<Vec<usize>>> {
+        let mut by_length: Vec<(usize, usize)> = texts
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| Ok((idx, self.tokenizer.count_tokens(text)?)))
+            .collect::<Result<_>>()?;
+        by_length.sort_by_key(|&(_, token_count)| token_count);
+
+        let mut batches = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+        let mut current_max_len = 0usize;
+
+        for (idx, token_count) in by_length {
+            let candidate_len = current_max_len.max(token_count);
+            let candidate_batch_len = current.len() + 1;
+            let over_token_budget = candidate_batch_len * candidate_len > self.max_tokens;
+            let over_batch_len = self
+                .max_batch_len
+                .is_some_and(|max| candidate_batch_len > max);
+
+            if !current.is_empty() && (over_token_budget || over_batch_len) {
+                batches.push(std::mem::take(&mut current));
+                current_max_len = 0;
+            }
+
+            current.push(idx);
+            current_max_len = current_max_len.max(token_count);
+        }
+
+        if !current.is_empty() {
+            batches.push(current);
+        }
+
+        Ok(batches)
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         let batch = self.tokenizer.encode(texts)?;
         let allocator = self.session.allocator();
         let ids: Vec<i64> = batch.input_ids.iter().map(|&v| v as i64).collect();
@@ -140,4 +244,36 @@ impl EmbeddingEngine for EmbeddingService {
     fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         self.embed(texts)
     }
+
+    fn model_id(&self) -> &str {
+        MODEL_ID
+    }
+}
+
+/// Embeds `texts` by splitting them into fixed-size batches and running the
+/// batches across a bounded rayon thread pool, preserving input order in the
+/// result. Intended for large documents where embedding sequentially would
+/// dominate upload latency; callers should run this from a blocking context
+/// (e.g. `spawn_blocking`) since it synchronously waits on the pool.
+pub fn embed_parallel(
+    engine: &(dyn EmbeddingEngine + Sync),
+    texts: &[String],
+    batch_size: usize,
+    parallelism: usize,
+) -> Result<Vec<Vec<f32>>> {
+    let batch_size = batch_size.max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(parallelism.max(1))
+        .build()
+        .context("failed to build embedding thread pool")?;
+
+    let batches: Vec<&[String]> = texts.chunks(batch_size).collect();
+    let batched = pool.install(|| {
+        batches
+            .into_par_iter()
+            .map(|batch| embed_with_retry(engine, batch))
+            .collect::<Result<Vec<Vec<Vec<f32>>>>>()
+    })?;
+
+    Ok(batched.into_iter().flatten().collect())
 }
@@ -0,0 +1,92 @@
+/// Coarse error classes surfaced to RPC clients via `error.data.class`, so
+/// they can branch on failure kind instead of pattern-matching message text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    NotFound,
+    InvalidData,
+    Encrypted,
+    EmbeddingFailed,
+    VectorStore,
+    Timeout,
+    Unknown,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::NotFound => "NotFound",
+            ErrorClass::InvalidData => "InvalidData",
+            ErrorClass::Encrypted => "Encrypted",
+            ErrorClass::EmbeddingFailed => "EmbeddingFailed",
+            ErrorClass::VectorStore => "VectorStore",
+            ErrorClass::Timeout => "Timeout",
+            ErrorClass::Unknown => "Unknown",
+        }
+    }
+
+    /// Whether a client can reasonably expect the same call to succeed on
+    /// retry without changing its input.
+    pub fn retryable(&self) -> bool {
+        matches!(self, ErrorClass::Timeout | ErrorClass::VectorStore)
+    }
+}
+
+/// Inspects the rendered error chain (not just the top-level message) to
+/// assign a class, since most of our errors are `anyhow::Context` wrapping a
+/// lower-level cause rather than a typed variant.
+pub fn error_class(err: &anyhow::Error) -> ErrorClass {
+    let chain: String = err
+        .chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<_>>()
+        .join(" | ")
+        .to_lowercase();
+
+    if chain.contains("encrypted") {
+        ErrorClass::Encrypted
+    } else if chain.contains("not found") || chain.contains("no such file") {
+        ErrorClass::NotFound
+    } else if chain.contains("timed out") || chain.contains("timeout") {
+        ErrorClass::Timeout
+    } else if chain.contains("embedding") || chain.contains("onnx") || chain.contains("tokeniz") {
+        ErrorClass::EmbeddingFailed
+    } else if chain.contains("qdrant") || chain.contains("collection") || chain.contains("vector") {
+        ErrorClass::VectorStore
+    } else if chain.contains("empty") || chain.contains("invalid") || chain.contains("parse") {
+        ErrorClass::InvalidData
+    } else {
+        ErrorClass::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn classifies_encrypted_pdfs() {
+        let err = anyhow!("pdf is encrypted and cannot be parsed");
+        assert_eq!(error_class(&err), ErrorClass::Encrypted);
+    }
+
+    #[test]
+    fn classifies_through_context_chain() {
+        let err = Err::<(), _>(anyhow!("file not found: doc.pdf"))
+            .context("pdf processing task failed")
+            .unwrap_err();
+        assert_eq!(error_class(&err), ErrorClass::NotFound);
+    }
+
+    #[test]
+    fn classifies_embedding_failures() {
+        let err = anyhow!("failed to execute ONNX session").context("embedding generation failed");
+        assert_eq!(error_class(&err), ErrorClass::EmbeddingFailed);
+    }
+
+    #[test]
+    fn unknown_errors_are_not_retryable() {
+        assert!(!ErrorClass::Unknown.retryable());
+        assert!(ErrorClass::Timeout.retryable());
+    }
+}
@@ -1,19 +1,63 @@
-use crate::document::{process_pdf, ProcessedDocument};
-use crate::embeddings::{EmbeddingEngine, EmbeddingService};
+use crate::document::{process_pdf, ChunkConfig, ProcessedDocument};
+use crate::embedding_cache::CachedEmbeddingEngine;
+use crate::embeddings::{embed_parallel, EmbeddingEngine, EmbeddingService, REQUEST_PARALLELISM};
+use crate::errors::error_class;
 use crate::qdrant::{EmbeddedQdrant, QdrantDocumentPayload};
+use crate::remote_embeddings::{RemoteEmbeddingConfig, RemoteEmbeddingService};
+use crate::retry::embed_with_retry;
 use crate::tokenizer::TokenizerService;
+use crate::transport::Transport;
 use anyhow::{anyhow, Context, Result};
+use futures::future::join_all;
+use rayon::prelude::*;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::io::{BufRead, BufReader, Write};
+use std::io::BufReader;
 use std::path::PathBuf;
 use tokio::runtime::Runtime;
 use tokio::task;
 use tracing::error;
 use uuid::Uuid;
 
+/// Below this many chunks, embedding sequentially is cheaper than paying for
+/// a rayon pool spin-up.
+const PARALLEL_CHUNK_THRESHOLD: usize = 32;
+const EMBEDDING_BATCH_SIZE: usize = 16;
+
+/// Protocol version advertised during the `initialize` handshake. Bump the
+/// major component only when a breaking change lands in the request/response
+/// shapes clients rely on; minor bumps are purely additive.
+const PROTOCOL_VERSION: &str = "1.0";
+
+const EMBEDDING_CACHE_FILE_NAME: &str = "embedding_cache.json";
+
+/// Where the content-hash embedding cache is persisted between runs, mirroring
+/// the `MODEL_BASE`-relative resolution `TokenizerService` uses for model
+/// assets so both live under the same data directory.
+fn embedding_cache_path() -> PathBuf {
+    let base = std::env::var("MODEL_BASE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_default().join("models"));
+    base.join(EMBEDDING_CACHE_FILE_NAME)
+}
+
+/// Picks a remote HTTP embedding provider when `LEXAI_EMBEDDING_ENDPOINT` is
+/// set, otherwise falls back to the local ONNX engine, which remains the
+/// default for installs that ship model assets.
+fn build_embedding_engine(
+    tokenizer: std::sync::Arc<TokenizerService>,
+) -> Result<std::sync::Arc<dyn EmbeddingEngine>> {
+    if let Some(config) = RemoteEmbeddingConfig::from_env() {
+        let service = RemoteEmbeddingService::new(config)
+            .context("failed to initialize remote embedding provider")?;
+        return Ok(std::sync::Arc::new(service));
+    }
+
+    Ok(std::sync::Arc::new(EmbeddingService::new(tokenizer)?))
+}
+
 pub struct JsonRpcLoop {
-    reader: BufReader<std::io::Stdin>,
+    transport: Transport<BufReader<std::io::Stdin>>,
     ctx: RpcContext,
     runtime: Runtime,
 }
@@ -21,13 +65,24 @@ pub struct JsonRpcLoop {
 pub struct RpcContext {
     pub qdrant: EmbeddedQdrant,
     pub embeddings: std::sync::Arc<dyn EmbeddingEngine>,
+    /// Same allocation as `embeddings`, kept as the concrete type so the
+    /// disk-persistence path (not part of the `EmbeddingEngine` trait) stays
+    /// reachable after each batch and on shutdown.
+    pub embedding_cache: std::sync::Arc<CachedEmbeddingEngine>,
+    pub tokenizer: std::sync::Arc<TokenizerService>,
+    pub chunk_config: ChunkConfig,
 }
 
 impl JsonRpcLoop {
     pub fn new() -> Result<Self> {
         let qdrant = EmbeddedQdrant::new()?;
         let tokenizer = std::sync::Arc::new(TokenizerService::new()?);
-        let embeddings = std::sync::Arc::new(EmbeddingService::new(tokenizer.clone())?);
+        let embedding_service = build_embedding_engine(tokenizer.clone())?;
+        let embedding_cache = std::sync::Arc::new(
+            CachedEmbeddingEngine::new(embedding_service)
+                .with_disk_persistence(embedding_cache_path()),
+        );
+        let embeddings: std::sync::Arc<dyn EmbeddingEngine> = embedding_cache.clone();
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .worker_threads(2)
@@ -35,9 +90,18 @@ impl JsonRpcLoop {
             .build()
             .context("failed to build tokio runtime")?;
 
+        let transport = Transport::sniff(BufReader::new(std::io::stdin()))
+            .context("failed to detect stdio transport framing")?;
+
         Ok(Self {
-            reader: BufReader::new(std::io::stdin()),
-            ctx: RpcContext { qdrant, embeddings },
+            transport,
+            ctx: RpcContext {
+                qdrant,
+                embeddings,
+                embedding_cache,
+                tokenizer,
+                chunk_config: ChunkConfig::default(),
+            },
             runtime,
         })
     }
@@ -46,37 +110,102 @@ impl JsonRpcLoop {
         let stdout = std::io::stdout();
         let mut writer = stdout.lock();
 
-        let mut line = String::new();
-        while self.reader.read_line(&mut line)? != 0 {
-            let response = self.handle_line(&line);
-            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
-            writer.flush()?;
-            line.clear();
+        while let Some(message) = self.transport.read_message()? {
+            if let Some(response) = self.runtime.block_on(self.handle_message(&message)) {
+                self.transport.write_message(&mut writer, &response)?;
+            }
+        }
+
+        // Best-effort final flush so embeddings computed this run are still
+        // on disk for the next one; a failure here shouldn't turn a clean
+        // shutdown into an error.
+        if let Err(err) = self.ctx.embedding_cache.persist() {
+            error!("embedding_cache.persist_on_shutdown_failed: {err:?}");
         }
         Ok(())
     }
 
-    fn handle_line(&mut self, line: &str) -> JsonRpcResponse {
-        let request: JsonRpcRequest = match serde_json::from_str(line) {
-            Ok(req) => req,
+    /// Parses one message, which per JSON-RPC 2.0 may be a single request
+    /// object or a batch array. Returns the text to write back, or `None`
+    /// when nothing should be written (an all-notification batch, or a lone
+    /// notification).
+    async fn handle_message(&self, message: &str) -> Option<String> {
+        let value: Value = match serde_json::from_str(message) {
+            Ok(v) => v,
             Err(err) => {
                 error!("jsonrpc.parse_error: {err}");
-                return JsonRpcResponse::error(Value::Null, -32700, &err.to_string());
+                let response = JsonRpcResponse::error(Value::Null, -32700, &err.to_string());
+                return Some(serde_json::to_string(&response).unwrap_or_default());
             }
         };
 
-        let id = request.id.clone().unwrap_or(Value::Null);
+        match value {
+            Value::Array(items) => {
+                if items.is_empty() {
+                    let response = JsonRpcResponse::error(Value::Null, -32600, "Invalid Request");
+                    return Some(serde_json::to_string(&response).unwrap_or_default());
+                }
+
+                let responses: Vec<JsonRpcResponse> =
+                    join_all(items.into_iter().map(|item| self.handle_one(item)))
+                        .await
+                        .into_iter()
+                        .flatten()
+                        .collect();
+
+                if responses.is_empty() {
+                    None
+                } else {
+                    Some(serde_json::to_string(&responses).unwrap_or_default())
+                }
+            }
+            single => self
+                .handle_one(single)
+                .await
+                .map(|response| serde_json::to_string(&response).unwrap_or_default()),
+        }
+    }
+
+    /// Handles a single request value from either a bare line or one element
+    /// of a batch array. Returns `None` when the request is a notification
+    /// (no `id` member), since JSON-RPC 2.0 forbids responding to those even
+    /// if they error.
+    async fn handle_one(&self, raw: Value) -> Option<JsonRpcResponse> {
+        let has_id_member = matches!(&raw, Value::Object(map) if map.contains_key("id"));
+        let is_notification = raw.is_object() && !has_id_member;
+
+        let request: JsonRpcRequest = match serde_json::from_value(raw) {
+            Ok(req) => req,
+            Err(err) => {
+                if is_notification {
+                    return None;
+                }
+                return Some(JsonRpcResponse::error(
+                    Value::Null,
+                    -32600,
+                    &format!("Invalid Request: {err}"),
+                ));
+            }
+        };
 
-        match self.dispatch(request) {
+        let id = request.id.clone().unwrap_or(Value::Null);
+        let response = match self.dispatch(request).await {
             Ok(response) => response,
             Err(err) => {
                 error!("jsonrpc.internal_error: {err}");
-                JsonRpcResponse::error(id, -32603, &err.to_string())
+                let class = error_class(&err);
+                JsonRpcResponse::error_classified(id, -32603, &err.to_string(), class)
             }
+        };
+
+        if is_notification {
+            None
+        } else {
+            Some(response)
         }
     }
 
-    fn dispatch(&mut self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+    async fn dispatch(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
         let id = request.id.unwrap_or(Value::Null);
         if request.jsonrpc != "2.0" {
             return Ok(JsonRpcResponse::error(
@@ -88,11 +217,12 @@ impl JsonRpcLoop {
 
         match request.method.as_str() {
             "ping" => Ok(JsonRpcResponse::result(id, json!({ "status": "ok" }))),
+            "initialize" => Ok(JsonRpcResponse::result(id, self.handle_initialize())),
             "upload_document" => {
                 let params = request.params.unwrap_or(Value::Null);
                 match serde_json::from_value::<UploadDocumentParams>(params) {
                     Ok(args) => {
-                        let result = self.runtime.block_on(self.handle_upload_document(args))?;
+                        let result = self.handle_upload_document(args).await?;
                         Ok(JsonRpcResponse::result(id, result))
                     }
                     Err(_) => Ok(JsonRpcResponse::error(id, -32602, "Invalid params")),
@@ -102,7 +232,7 @@ impl JsonRpcLoop {
                 let params = request.params.unwrap_or(Value::Null);
                 match serde_json::from_value::<SearchParams>(params) {
                     Ok(args) => {
-                        let result = self.runtime.block_on(self.handle_search_document(args))?;
+                        let result = self.handle_search_document(args).await?;
                         Ok(JsonRpcResponse::result(id, result))
                     }
                     Err(_) => Ok(JsonRpcResponse::error(id, -32602, "Invalid params")),
@@ -112,56 +242,161 @@ impl JsonRpcLoop {
         }
     }
 
+    /// Reports the worker's protocol version and feature capabilities so the
+    /// client can refuse to talk to an incompatible build instead of failing
+    /// on the first real call with an opaque parse error.
+    fn handle_initialize(&self) -> Value {
+        json!({
+            "protocol_version": PROTOCOL_VERSION,
+            "capabilities": {
+                "embeddings": true,
+                "rerank": false,
+                "ocr": false,
+            },
+        })
+    }
+
     async fn handle_upload_document(&self, params: UploadDocumentParams) -> Result<Value> {
         let document_id = params
             .document_id
             .unwrap_or_else(|| Uuid::new_v4().to_string());
 
         let file_path = PathBuf::from(params.file_path);
-        let processed: ProcessedDocument = task::spawn_blocking(move || process_pdf(&file_path))
-            .await
-            .context("pdf processing task failed")??;
+        let tokenizer = self.ctx.tokenizer.clone();
+        let chunk_config = self.ctx.chunk_config;
+        let processed: ProcessedDocument =
+            task::spawn_blocking(move || process_pdf(&file_path, &tokenizer, &chunk_config))
+                .await
+                .context("pdf processing task failed")??;
 
         if processed.chunks.is_empty() {
             return Err(anyhow!("no chunks generated for document"));
         }
 
-        let embeddings = self
-            .ctx
-            .embeddings
-            .embed(&processed.chunks)
-            .context("embedding generation failed")?;
-
-        self.ctx.qdrant.ensure_collection().await?;
-        let mut points = Vec::with_capacity(embeddings.len());
-        for (vector, chunk) in embeddings.into_iter().zip(processed.chunks.iter()) {
-            points.push(self.ctx.qdrant.build_point(
-                vector,
-                QdrantDocumentPayload {
-                    document_id: document_id.clone(),
-                    chunk_text: chunk.clone(),
-                },
-            )?);
+        let chunk_hashes: Vec<String> = processed
+            .chunks
+            .iter()
+            .map(|chunk| blake3::hash(chunk.as_bytes()).to_hex().to_string())
+            .collect();
+
+        let known: std::collections::HashSet<&str> = params
+            .known_chunk_hashes
+            .iter()
+            .map(String::as_str)
+            .collect();
+
+        let novel_indices: Vec<usize> = (0..processed.chunks.len())
+            .filter(|&idx| !known.contains(chunk_hashes[idx].as_str()))
+            .collect();
+        let novel_chunks: Vec<String> = novel_indices
+            .iter()
+            .map(|&idx| processed.chunks[idx].clone())
+            .collect();
+        let novel_hashes: Vec<String> = novel_indices
+            .iter()
+            .map(|&idx| chunk_hashes[idx].clone())
+            .collect();
+
+        if !novel_chunks.is_empty() {
+            let embeddings = if novel_chunks.len() >= PARALLEL_CHUNK_THRESHOLD {
+                let engine = self.ctx.embeddings.clone();
+                let chunks = novel_chunks.clone();
+                task::spawn_blocking(move || {
+                    embed_parallel(
+                        engine.as_ref(),
+                        &chunks,
+                        EMBEDDING_BATCH_SIZE,
+                        REQUEST_PARALLELISM,
+                    )
+                })
+                .await
+                .context("parallel embedding task failed")??
+            } else {
+                let engine = self.ctx.embeddings.clone();
+                let chunks = novel_chunks.clone();
+                task::spawn_blocking(move || embed_with_retry(engine.as_ref(), &chunks))
+                    .await
+                    .context("embedding task failed")?
+                    .context("embedding generation failed")?
+            };
+
+            // Persist right after computing fresh vectors rather than waiting
+            // for shutdown, so a crash mid-session doesn't lose embeddings a
+            // later restart would otherwise recompute. Rewriting the whole
+            // cache file is synchronous I/O proportional to cache size, so it
+            // runs on a blocking thread rather than stalling the runtime
+            // worker handling other pipelined requests.
+            let cache = self.ctx.embedding_cache.clone();
+            match task::spawn_blocking(move || cache.persist()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => error!("embedding_cache.persist_failed: {err:?}"),
+                Err(err) => error!("embedding_cache.persist_task_failed: {err:?}"),
+            }
+
+            self.ctx.qdrant.ensure_collection().await?;
+            let points = self.build_points(
+                &document_id,
+                embeddings,
+                &novel_chunks,
+                &novel_hashes,
+                &novel_indices,
+            )?;
+            self.ctx.qdrant.upsert_points(points).await?;
         }
-        self.ctx.qdrant.upsert_points(points).await?;
 
         Ok(json!({
             "document_id": document_id,
             "status": "processed",
             "chunk_count": processed.chunks.len(),
+            "embedded_chunk_count": novel_chunks.len(),
             "extracted_text": processed.text,
+            "chunk_hashes": chunk_hashes,
         }))
     }
 
+    /// Builds one Qdrant point per (vector, chunk, hash, index) quadruple on
+    /// rayon's global pool, preserving the original chunk order in the
+    /// returned `Vec` even though the work itself runs out of order.
+    /// `chunk_indices` carries each chunk's position in the full document
+    /// (not just within this batch of novel chunks), so retrieval can place
+    /// points from the same document back in order.
+    fn build_points(
+        &self,
+        document_id: &str,
+        embeddings: Vec<Vec<f32>>,
+        chunks: &[String],
+        chunk_hashes: &[String],
+        chunk_indices: &[usize],
+    ) -> Result<Vec<qdrant_client::qdrant::PointStruct>> {
+        embeddings
+            .into_par_iter()
+            .zip(chunks.par_iter())
+            .zip(chunk_hashes.par_iter())
+            .zip(chunk_indices.par_iter())
+            .map(|(((vector, chunk), chunk_hash), &chunk_index)| {
+                self.ctx.qdrant.build_point(
+                    vector,
+                    QdrantDocumentPayload {
+                        document_id: document_id.to_string(),
+                        chunk_text: chunk.clone(),
+                        chunk_hash: chunk_hash.clone(),
+                        chunk_index,
+                    },
+                )
+            })
+            .collect()
+    }
+
     async fn handle_search_document(&self, params: SearchParams) -> Result<Value> {
         if params.query.is_empty() {
             return Err(anyhow!("query text cannot be empty"));
         }
 
-        let query_vec = self
-            .ctx
-            .embeddings
-            .embed(&[params.query.clone()])
+        let engine = self.ctx.embeddings.clone();
+        let query = params.query.clone();
+        let query_vec = task::spawn_blocking(move || embed_with_retry(engine.as_ref(), &[query]))
+            .await
+            .context("embedding task failed")?
             .context("failed to embed query")?
             .into_iter()
             .next()
@@ -176,7 +411,14 @@ impl JsonRpcLoop {
         let results = self
             .ctx
             .qdrant
-            .search(query_vec, params.limit.unwrap_or(5), filter)
+            .hybrid_search(
+                &params.query,
+                query_vec,
+                params.limit.unwrap_or(5),
+                filter,
+                params.rrf_k,
+                params.semantic_weight,
+            )
             .await?;
 
         Ok(json!({ "results": results }))
@@ -187,26 +429,100 @@ impl JsonRpcLoop {
 mod tests {
     use super::*;
 
-    fn response_error_code(response: &JsonRpcResponse) -> Option<i32> {
-        response.error.as_ref().map(|e| e.code)
-    }
-
-    #[test]
-    fn parse_error_for_invalid_json() {
-        let mut loop_instance = JsonRpcLoop {
-            reader: BufReader::new(std::io::stdin()),
+    fn new_loop_instance() -> JsonRpcLoop {
+        let tokenizer = std::sync::Arc::new(TokenizerService::new().unwrap());
+        let embeddings = std::sync::Arc::new(EmbeddingService::new(tokenizer.clone()).unwrap());
+        JsonRpcLoop {
+            transport: Transport::new(BufReader::new(std::io::stdin()), crate::transport::TransportMode::LineDelimited),
             ctx: RpcContext {
                 qdrant: EmbeddedQdrant::new().unwrap(),
-                embeddings: std::sync::Arc::new(
-                    EmbeddingService::new(std::sync::Arc::new(TokenizerService::new().unwrap()))
-                        .unwrap(),
-                ),
+                embedding_cache: std::sync::Arc::new(CachedEmbeddingEngine::new(embeddings.clone())),
+                embeddings,
+                tokenizer,
+                chunk_config: ChunkConfig::default(),
             },
             runtime: tokio::runtime::Runtime::new().unwrap(),
-        };
+        }
+    }
+
+    fn error_code(response_json: &str) -> Option<i32> {
+        let value: Value = serde_json::from_str(response_json).unwrap();
+        value.get("error")?.get("code")?.as_i64().map(|v| v as i32)
+    }
+
+    #[test]
+    fn parse_error_for_invalid_json() {
+        let loop_instance = new_loop_instance();
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message("{invalid json}"))
+            .unwrap();
+        assert_eq!(error_code(&response), Some(-32700));
+    }
+
+    #[test]
+    fn notification_without_id_produces_no_output() {
+        let loop_instance = new_loop_instance();
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message(r#"{"jsonrpc":"2.0","method":"ping"}"#));
+        assert!(response.is_none());
+    }
 
-        let response = loop_instance.handle_line("{invalid json}");
-        assert_eq!(response_error_code(&response), Some(-32700));
+    #[test]
+    fn empty_batch_is_invalid_request() {
+        let loop_instance = new_loop_instance();
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message("[]"))
+            .unwrap();
+        assert_eq!(error_code(&response), Some(-32600));
+    }
+
+    #[test]
+    fn batch_of_only_notifications_produces_no_output() {
+        let loop_instance = new_loop_instance();
+        let line = r#"[{"jsonrpc":"2.0","method":"ping"},{"jsonrpc":"2.0","method":"ping"}]"#;
+        let response = loop_instance.runtime.block_on(loop_instance.handle_message(line));
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn batch_returns_responses_only_for_requests_with_id() {
+        let loop_instance = new_loop_instance();
+        let line = r#"[{"jsonrpc":"2.0","method":"ping","id":1},{"jsonrpc":"2.0","method":"ping"}]"#;
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message(line))
+            .unwrap();
+        let parsed: Vec<Value> = serde_json::from_str(&response).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0]["id"], json!(1));
+    }
+
+    #[test]
+    fn initialize_reports_protocol_version_and_capabilities() {
+        let loop_instance = new_loop_instance();
+        let line = r#"{"jsonrpc":"2.0","method":"initialize","id":1}"#;
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message(line))
+            .unwrap();
+        let value: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(value["result"]["protocol_version"], json!(PROTOCOL_VERSION));
+        assert_eq!(value["result"]["capabilities"]["embeddings"], json!(true));
+    }
+
+    #[test]
+    fn internal_error_carries_classified_data() {
+        let loop_instance = new_loop_instance();
+        let line = r#"{"jsonrpc":"2.0","method":"upload_document","params":{"file_path":"/no/such/file.pdf"},"id":1}"#;
+        let response = loop_instance
+            .runtime
+            .block_on(loop_instance.handle_message(line))
+            .unwrap();
+        let value: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(value["error"]["data"]["class"], json!("NotFound"));
     }
 }
 
@@ -223,6 +539,11 @@ struct UploadDocumentParams {
     #[serde(default)]
     document_id: Option<String>,
     file_path: String,
+    /// Hex-encoded blake3 hashes of chunks the caller has already indexed
+    /// elsewhere (from `known_chunks`); matching chunks are skipped during
+    /// embedding and indexing.
+    #[serde(default)]
+    known_chunk_hashes: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -232,6 +553,13 @@ struct SearchParams {
     document_id: Option<String>,
     #[serde(default)]
     limit: Option<u64>,
+    /// Reciprocal Rank Fusion constant; defaults to 60 when omitted.
+    #[serde(default)]
+    rrf_k: Option<f64>,
+    /// Weight given to the semantic (vector) ranked list versus the keyword
+    /// one, from 0.0 (keyword-only) to 1.0 (semantic-only); defaults to 0.5.
+    #[serde(default)]
+    semantic_weight: Option<f64>,
 }
 
 #[derive(serde::Serialize)]
@@ -266,6 +594,29 @@ impl JsonRpcResponse {
         }
     }
 
+    /// Like `error`, but attaches the failure's `class`/`retryable` taxonomy
+    /// so clients can branch on failure kind instead of message text.
+    fn error_classified(
+        id: Value,
+        code: i32,
+        message: &str,
+        class: crate::errors::ErrorClass,
+    ) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.to_owned(),
+                data: Some(json!({
+                    "class": class.as_str(),
+                    "retryable": class.retryable(),
+                })),
+            }),
+            id,
+        }
+    }
+
     fn result(id: Value, value: Value) -> Self {
         Self {
             jsonrpc: "2.0",
@@ -0,0 +1,11 @@
+pub mod document;
+pub mod embedding_cache;
+pub mod embeddings;
+pub mod errors;
+pub mod jsonrpc;
+pub mod manager;
+pub mod qdrant;
+pub mod remote_embeddings;
+pub mod retry;
+pub mod tokenizer;
+pub mod transport;
@@ -1,7 +1,8 @@
 use anyhow::{anyhow, Context, Result};
 use qdrant_client::qdrant::{
-    condition::ConditionOneOf, point_id, r#match::MatchValue, value::Kind, Condition, Distance,
-    FieldCondition, Filter, ListValue, Match, PointStruct, SearchPointsBuilder, Struct,
+    condition::ConditionOneOf, point_id, r#match::MatchValue, value::Kind, Condition,
+    CreateFieldIndexCollectionBuilder, Distance, FieldCondition, FieldType, Filter, ListValue,
+    Match, PointId, PointStruct, ScrollPointsBuilder, SearchPointsBuilder, Struct,
     UpsertPointsBuilder, Value as QdrantValue, VectorParamsBuilder, Vectors,
 };
 use qdrant_client::Qdrant;
@@ -10,7 +11,21 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 const COLLECTION_NAME: &str = "lexai_documents";
-const VECTOR_DIM: usize = 384;
+pub(crate) const VECTOR_DIM: usize = 384;
+
+/// Default Reciprocal Rank Fusion constant. Larger `k` flattens the
+/// contribution of rank differences near the top of each list; 60 is the
+/// standard value from the original RRF paper and most hybrid-search
+/// implementations that cite it.
+const DEFAULT_RRF_K: f64 = 60.0;
+
+/// Default semantic-vs-keyword weighting for `hybrid_search`: an even split
+/// between the two ranked lists.
+const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.5;
+
+/// How many candidates to pull from each ranked list before fusing, wider
+/// than the requested `limit` so RRF has enough of each list to draw from.
+const CANDIDATE_POOL_MULTIPLIER: u64 = 4;
 
 pub struct EmbeddedQdrant {
     client: Qdrant,
@@ -49,6 +64,16 @@ impl EmbeddedQdrant {
                     )),
                 });
             self.client.create_collection(request).await?;
+
+            // Backs the keyword half of `hybrid_search`; without a text
+            // index, `Match::Text` filters can't be evaluated server-side.
+            self.client
+                .create_field_index(CreateFieldIndexCollectionBuilder::new(
+                    COLLECTION_NAME,
+                    "chunk_text",
+                    FieldType::Text,
+                ))
+                .await?;
         }
         Ok(())
     }
@@ -91,6 +116,141 @@ impl EmbeddedQdrant {
         Ok(results)
     }
 
+    /// Runs vector search and a keyword search over `chunk_text` in
+    /// parallel, then fuses the two ranked lists with Reciprocal Rank
+    /// Fusion: each point's score is `sum over lists of weight / (k + rank)`,
+    /// `rank` being its 1-based position in that list. Points missing from a
+    /// list simply don't contribute a term from it. `k` defaults to 60 (the
+    /// standard RRF constant) and `semantic_weight` to an even 0.5/0.5 split
+    /// between vector and keyword results; both can be tuned by callers that
+    /// want to bias toward lexical or semantic matching.
+    pub async fn hybrid_search(
+        &self,
+        query_text: &str,
+        vector: Vec<f32>,
+        limit: u64,
+        filter: Option<Filter>,
+        k: Option<f64>,
+        semantic_weight: Option<f64>,
+    ) -> Result<Vec<Value>> {
+        let k = k.unwrap_or(DEFAULT_RRF_K);
+        let semantic_weight = semantic_weight.unwrap_or(DEFAULT_SEMANTIC_WEIGHT);
+        let keyword_weight = 1.0 - semantic_weight;
+        let candidate_limit = limit.max(1) * CANDIDATE_POOL_MULTIPLIER;
+
+        let (semantic_results, keyword_results) = tokio::try_join!(
+            self.search_ranked(vector, candidate_limit, filter.clone()),
+            self.keyword_search(query_text, candidate_limit, filter),
+        )?;
+
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        let mut payloads: HashMap<String, Value> = HashMap::new();
+
+        for (list, weight) in [
+            (semantic_results, semantic_weight),
+            (keyword_results, keyword_weight),
+        ] {
+            for (rank, (id, payload)) in list.into_iter().enumerate() {
+                *scores.entry(id.clone()).or_insert(0.0) += weight / (k + (rank + 1) as f64);
+                payloads.entry(id).or_insert(payload);
+            }
+        }
+
+        let mut ranked: Vec<(String, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(ranked
+            .into_iter()
+            .take(limit as usize)
+            .filter_map(|(id, _)| payloads.remove(&id))
+            .collect())
+    }
+
+    /// Like `search`, but also returns each point's ID so `hybrid_search` can
+    /// key contributions from both ranked lists by the same point.
+    async fn search_ranked(
+        &self,
+        vector: Vec<f32>,
+        limit: u64,
+        filter: Option<Filter>,
+    ) -> Result<Vec<(String, Value)>> {
+        let mut builder =
+            SearchPointsBuilder::new(COLLECTION_NAME, vector, limit).with_payload(true);
+        if let Some(f) = filter {
+            builder = builder.filter(f);
+        }
+
+        let response = self.client.search_points(builder).await?;
+        Ok(response
+            .result
+            .into_iter()
+            .map(|point| {
+                let id = point_id_to_string(point.id);
+                let payload = qdrant_payload_to_value(point.payload).unwrap_or(Value::Null);
+                (id, payload)
+            })
+            .collect())
+    }
+
+    /// Scrolls points whose `chunk_text` payload matches `query_text` via
+    /// the server-side full-text index, then ranks the candidates ourselves
+    /// by term-frequency: `scroll` only filters, it does not return matches
+    /// in relevance order, and RRF needs a real rank position to fuse on.
+    async fn keyword_search(
+        &self,
+        query_text: &str,
+        limit: u64,
+        filter: Option<Filter>,
+    ) -> Result<Vec<(String, Value)>> {
+        let mut must = vec![Condition {
+            condition_one_of: Some(ConditionOneOf::Field(FieldCondition {
+                key: "chunk_text".to_string(),
+                r#match: Some(Match {
+                    match_value: Some(MatchValue::Text(query_text.to_string())),
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }];
+        if let Some(f) = filter {
+            must.extend(f.must);
+        }
+
+        let request = ScrollPointsBuilder::new(COLLECTION_NAME)
+            .filter(Filter {
+                must,
+                ..Default::default()
+            })
+            .limit(limit as u32)
+            .with_payload(true)
+            .build();
+
+        let response = self.client.scroll(request).await?;
+        let query_tokens = tokenize_query(query_text);
+
+        let mut scored: Vec<(f64, String, Value)> = response
+            .result
+            .into_iter()
+            .map(|point| {
+                let id = point_id_to_string(point.id);
+                let payload = qdrant_payload_to_value(point.payload).unwrap_or(Value::Null);
+                let chunk_text = payload
+                    .get("chunk_text")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                let score = term_frequency_score(&query_tokens, chunk_text);
+                (score, id, payload)
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(scored
+            .into_iter()
+            .map(|(_, id, payload)| (id, payload))
+            .collect())
+    }
+
     pub async fn upsert_points(&self, points: Vec<PointStruct>) -> Result<()> {
         let request = UpsertPointsBuilder::new(COLLECTION_NAME, points)
             .wait(true)
@@ -114,6 +274,51 @@ impl EmbeddedQdrant {
     }
 }
 
+/// Lowercases `query` and splits it into alphanumeric tokens for the
+/// term-frequency scoring `keyword_search` ranks candidates by.
+fn tokenize_query(query: &str) -> Vec<String> {
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Fraction of `text`'s words that match one of `query_tokens`, used as a
+/// lightweight, corpus-statistics-free stand-in for a lexical relevance
+/// score (full BM25 needs collection-wide term/doc-length statistics that
+/// aren't available from a single `scroll` page).
+fn term_frequency_score(query_tokens: &[String], text: &str) -> f64 {
+    if query_tokens.is_empty() {
+        return 0.0;
+    }
+
+    let text_lower = text.to_lowercase();
+    let words: Vec<&str> = text_lower.split_whitespace().collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    let matches = words
+        .iter()
+        .filter(|word| {
+            let trimmed = word.trim_matches(|c: char| !c.is_alphanumeric());
+            query_tokens.iter().any(|token| token == trimmed)
+        })
+        .count();
+
+    matches as f64 / words.len() as f64
+}
+
+fn point_id_to_string(id: Option<PointId>) -> String {
+    match id.and_then(|id| id.point_id_options) {
+        Some(point_id::PointIdOptions::Uuid(uuid)) => uuid,
+        Some(point_id::PointIdOptions::Num(num)) => num.to_string(),
+        None => String::new(),
+    }
+}
+
 fn qdrant_payload_to_value(payload: HashMap<String, QdrantValue>) -> Result<Value> {
     let mut map = serde_json::Map::new();
     for (key, value) in payload {
@@ -195,4 +400,10 @@ fn json_to_qdrant_value(value: Value) -> Result<QdrantValue> {
 pub struct QdrantDocumentPayload {
     pub document_id: String,
     pub chunk_text: String,
+    pub chunk_hash: String,
+    /// Position of this chunk within the source document, so a long
+    /// document that produced several points (including ones exploded by
+    /// `TokenizerService::chunk_text`) can be reassembled or ordered on
+    /// retrieval.
+    pub chunk_index: usize,
 }
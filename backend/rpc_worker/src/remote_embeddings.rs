@@ -0,0 +1,207 @@
+use crate::embeddings::EmbeddingEngine;
+use crate::qdrant::VECTOR_DIM;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Attempts before giving up on a single embedding call, matching the
+/// attempt budget `retry.rs` uses for the local engine.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Which remote API shape to speak.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteEmbeddingApi {
+    /// OpenAI's `/v1/embeddings` shape, also spoken by most drop-in proxies.
+    OpenAiCompatible,
+    /// Ollama's `/api/embeddings`, which embeds one prompt per request.
+    Ollama,
+}
+
+pub struct RemoteEmbeddingConfig {
+    pub api: RemoteEmbeddingApi,
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+impl RemoteEmbeddingConfig {
+    /// Reads `LEXAI_EMBEDDING_ENDPOINT`, `LEXAI_EMBEDDING_PROVIDER` (`openai`
+    /// or `ollama`, default `openai`), `LEXAI_EMBEDDING_MODEL`, and
+    /// `LEXAI_EMBEDDING_API_KEY` from the environment. Returns `None` when no
+    /// endpoint is configured, so callers default to the local ONNX engine.
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("LEXAI_EMBEDDING_ENDPOINT").ok()?;
+        let api = match std::env::var("LEXAI_EMBEDDING_PROVIDER").as_deref() {
+            Ok("ollama") => RemoteEmbeddingApi::Ollama,
+            _ => RemoteEmbeddingApi::OpenAiCompatible,
+        };
+        let model = std::env::var("LEXAI_EMBEDDING_MODEL")
+            .unwrap_or_else(|_| "text-embedding-3-small".to_string());
+        let api_key = std::env::var("LEXAI_EMBEDDING_API_KEY").ok();
+
+        Some(Self {
+            api,
+            endpoint,
+            model,
+            api_key,
+        })
+    }
+}
+
+/// `EmbeddingEngine` backed by an HTTP embedding API, for deployments without
+/// local model assets. Requests are synchronous (`reqwest::blocking`) to
+/// match the trait's sync signature; callers already run ONNX embedding
+/// inside `spawn_blocking`, so the same applies here.
+pub struct RemoteEmbeddingService {
+    config: RemoteEmbeddingConfig,
+    client: reqwest::blocking::Client,
+    model_id: String,
+}
+
+impl RemoteEmbeddingService {
+    pub fn new(config: RemoteEmbeddingConfig) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("failed to build HTTP client for remote embedding provider")?;
+        let provider = match config.api {
+            RemoteEmbeddingApi::OpenAiCompatible => "remote-openai",
+            RemoteEmbeddingApi::Ollama => "remote-ollama",
+        };
+        let model_id = format!("{provider}:{}", config.model);
+        Ok(Self {
+            config,
+            client,
+            model_id,
+        })
+    }
+
+    fn embed_openai(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        // text-embedding-3-* (the documented default model) serve 1536 dims
+        // unless truncated via `dimensions`, but the Qdrant collection is
+        // fixed at VECTOR_DIM to match the local ONNX model. Request the
+        // matching size directly instead of letting `enforce_dim` reject
+        // every call against the default model.
+        let body = json!({
+            "model": self.config.model,
+            "input": texts,
+            "dimensions": VECTOR_DIM,
+        });
+        let response = self.post_with_retry(&self.config.endpoint, &body)?;
+
+        let data = response["data"]
+            .as_array()
+            .ok_or_else(|| anyhow!("remote embedding response missing `data` array"))?;
+
+        let mut vectors: Vec<(usize, Vec<f32>)> = data
+            .iter()
+            .enumerate()
+            .map(|(fallback_idx, entry)| {
+                let index = entry["index"]
+                    .as_u64()
+                    .map(|idx| idx as usize)
+                    .unwrap_or(fallback_idx);
+                Ok((index, parse_vector(&entry["embedding"])?))
+            })
+            .collect::<Result<_>>()?;
+
+        vectors.sort_by_key(|(index, _)| *index);
+        vectors
+            .into_iter()
+            .map(|(_, vector)| enforce_dim(vector))
+            .collect()
+    }
+
+    fn embed_ollama(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        texts
+            .iter()
+            .map(|text| {
+                let body = json!({ "model": self.config.model, "prompt": text });
+                let response = self.post_with_retry(&self.config.endpoint, &body)?;
+                enforce_dim(parse_vector(&response["embedding"])?)
+            })
+            .collect()
+    }
+
+    /// POSTs `body` to `url`, retrying HTTP 429/5xx responses with
+    /// exponential backoff. Honors a `Retry-After` header (seconds) when
+    /// present instead of the computed backoff.
+    fn post_with_retry(&self, url: &str, body: &Value) -> Result<Value> {
+        let mut attempt = 0u32;
+
+        loop {
+            let mut request = self.client.post(url).json(body);
+            if let Some(api_key) = &self.config.api_key {
+                request = request.bearer_auth(api_key);
+            }
+
+            let response = request
+                .send()
+                .context("remote embedding request failed")?;
+
+            if response.status().is_success() {
+                return response
+                    .json::<Value>()
+                    .context("failed to parse remote embedding response");
+            }
+
+            let status = response.status();
+            attempt += 1;
+            let retryable = status.as_u16() == 429 || status.is_server_error();
+            if !retryable || attempt >= MAX_ATTEMPTS {
+                let body_text = response.text().unwrap_or_default();
+                bail!("remote embedding provider returned {status}: {body_text}");
+            }
+
+            let wait = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or_else(|| Duration::from_millis(200 * 2u64.pow(attempt)));
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+impl EmbeddingEngine for RemoteEmbeddingService {
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match self.config.api {
+            RemoteEmbeddingApi::OpenAiCompatible => self.embed_openai(texts),
+            RemoteEmbeddingApi::Ollama => self.embed_ollama(texts),
+        }
+    }
+
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+}
+
+fn parse_vector(value: &Value) -> Result<Vec<f32>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("remote embedding response missing an `embedding` array"))?
+        .iter()
+        .map(|component| {
+            component
+                .as_f64()
+                .map(|v| v as f32)
+                .ok_or_else(|| anyhow!("non-numeric value in embedding vector"))
+        })
+        .collect()
+}
+
+fn enforce_dim(vector: Vec<f32>) -> Result<Vec<f32>> {
+    if vector.len() != VECTOR_DIM {
+        bail!(
+            "remote embedding provider returned a {}-dim vector, expected {VECTOR_DIM}",
+            vector.len()
+        );
+    }
+    Ok(vector)
+}
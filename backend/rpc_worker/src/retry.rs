@@ -0,0 +1,150 @@
+use crate::embeddings::EmbeddingEngine;
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 5;
+
+/// What an embedding call should do after a given failure. New backends can
+/// extend the mapping in `classify` without touching the retry loop itself.
+#[derive(Debug, PartialEq, Eq)]
+enum RetryStrategy {
+    GiveUp,
+    Retry,
+    RetryTokenized,
+    RetryAfterRateLimit,
+}
+
+/// Errors are classified by inspecting their rendered message, mirroring the
+/// ad-hoc string matching already used elsewhere in the RPC worker (e.g. the
+/// encrypted-PDF detection in `rust_core`) until backends report structured
+/// error codes.
+fn classify(err: &anyhow::Error) -> RetryStrategy {
+    let message = err.to_string().to_lowercase();
+
+    if message.contains("rate limit") || message.contains("429") {
+        RetryStrategy::RetryAfterRateLimit
+    } else if message.contains("too long")
+        || message.contains("context length")
+        || message.contains("maximum sequence length")
+    {
+        RetryStrategy::RetryTokenized
+    } else if message.contains("timed out")
+        || message.contains("connection")
+        || message.contains("temporarily unavailable")
+    {
+        RetryStrategy::Retry
+    } else {
+        RetryStrategy::GiveUp
+    }
+}
+
+/// Shortens an over-long chunk so a `RetryTokenized` retry has a realistic
+/// chance of succeeding. Halving by character is crude but backend-agnostic.
+fn shorten(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let half = (chars.len() / 2).max(1);
+    chars[..half].iter().collect()
+}
+
+/// Embeds `texts` through `engine`, retrying transient failures with the
+/// strategy `classify` assigns to each error. Gives up immediately on
+/// malformed input and surfaces the final error once attempts are exhausted.
+pub fn embed_with_retry(engine: &dyn EmbeddingEngine, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let mut attempt = 0u32;
+    let mut working: Vec<String> = texts.to_vec();
+
+    loop {
+        match engine.embed(&working) {
+            Ok(vectors) => return Ok(vectors),
+            Err(err) => {
+                attempt += 1;
+                if attempt >= MAX_ATTEMPTS {
+                    return Err(err).context("embedding failed after exhausting retry attempts");
+                }
+
+                match classify(&err) {
+                    RetryStrategy::GiveUp => return Err(err),
+                    RetryStrategy::Retry => {
+                        std::thread::sleep(Duration::from_millis(10u64.pow(attempt)));
+                    }
+                    RetryStrategy::RetryTokenized => {
+                        working = working.iter().map(|t| shorten(t)).collect();
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                    RetryStrategy::RetryAfterRateLimit => {
+                        std::thread::sleep(Duration::from_millis(100 + 10u64.pow(attempt)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_rate_limit_errors() {
+        let err = anyhow::anyhow!("provider returned 429 rate limit exceeded");
+        assert_eq!(classify(&err), RetryStrategy::RetryAfterRateLimit);
+    }
+
+    #[test]
+    fn classifies_too_long_errors() {
+        let err = anyhow::anyhow!("input exceeds maximum sequence length");
+        assert_eq!(classify(&err), RetryStrategy::RetryTokenized);
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        let err = anyhow::anyhow!("connection reset by peer");
+        assert_eq!(classify(&err), RetryStrategy::Retry);
+    }
+
+    #[test]
+    fn classifies_unknown_errors_as_give_up() {
+        let err = anyhow::anyhow!("malformed request body");
+        assert_eq!(classify(&err), RetryStrategy::GiveUp);
+    }
+
+    #[test]
+    fn shorten_halves_the_input() {
+        let text = "a".repeat(10);
+        assert_eq!(shorten(&text).len(), 5);
+    }
+
+    struct FlakyEngine {
+        failures_left: std::sync::atomic::AtomicU32,
+    }
+
+    impl EmbeddingEngine for FlakyEngine {
+        fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            if self
+                .failures_left
+                .fetch_update(
+                    std::sync::atomic::Ordering::SeqCst,
+                    std::sync::atomic::Ordering::SeqCst,
+                    |v| if v > 0 { Some(v - 1) } else { None },
+                )
+                .is_ok()
+            {
+                return Err(anyhow::anyhow!("connection reset by peer"));
+            }
+            Ok(texts.iter().map(|_| vec![0.0f32; 4]).collect())
+        }
+
+        fn model_id(&self) -> &str {
+            "test-flaky-engine"
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures_until_success() {
+        let engine = FlakyEngine {
+            failures_left: std::sync::atomic::AtomicU32::new(2),
+        };
+        let result = embed_with_retry(&engine, &["hello".to_string()]).unwrap();
+        assert_eq!(result.len(), 1);
+    }
+}
@@ -27,6 +27,17 @@ pub struct EncodedBatch {
     pub sequence_length: usize,
 }
 
+/// Controls how a batch is padded before tokenization output is assembled.
+/// `BatchLongest` pads every sequence to the longest one in the batch, which
+/// keeps the ONNX tensor small for batches of short inputs; `Fixed` always
+/// pads to the tokenizer's configured `model_max_length`, useful when a
+/// caller needs a stable shape across batches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaddingMode {
+    Fixed,
+    BatchLongest,
+}
+
 #[derive(Clone)]
 pub struct TokenizerService {
     tokenizer: Tokenizer,
@@ -65,7 +76,18 @@ impl TokenizerService {
         Ok(base)
     }
 
+    /// Encodes `texts` using `PaddingMode::BatchLongest`, the right default
+    /// for embedding inference where most batches are far shorter than the
+    /// model's maximum sequence length.
     pub fn encode(&self, texts: &[String]) -> Result<EncodedBatch> {
+        self.encode_with_padding(texts, PaddingMode::BatchLongest)
+    }
+
+    pub fn encode_with_padding(
+        &self,
+        texts: &[String],
+        padding: PaddingMode,
+    ) -> Result<EncodedBatch> {
         if texts.is_empty() {
             anyhow::bail!("no texts provided for encoding");
         }
@@ -76,8 +98,13 @@ impl TokenizerService {
             .token_to_id(&self.config.pad_token)
             .context("pad token missing from tokenizer")?;
 
+        let strategy = match padding {
+            PaddingMode::Fixed => PaddingStrategy::Fixed(max_length),
+            PaddingMode::BatchLongest => PaddingStrategy::BatchLongest,
+        };
+
         tokenizer.with_padding(Some(PaddingParams {
-            strategy: PaddingStrategy::Fixed(max_length),
+            strategy,
             direction: PaddingDirection::Right,
             pad_to_multiple_of: None,
             pad_id,
@@ -99,7 +126,12 @@ impl TokenizerService {
             .map_err(|e| anyhow!("tokenization failed: {e}"))?;
 
         let batch_len = encodings.len();
-        let sequence_length = max_length;
+        let sequence_length = match padding {
+            PaddingMode::Fixed => max_length,
+            PaddingMode::BatchLongest => {
+                encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0)
+            }
+        };
 
         let input_ids: Vec<i64> = encodings
             .iter()
@@ -128,4 +160,66 @@ impl TokenizerService {
     pub fn model_path(model_file: &str) -> Result<PathBuf> {
         Ok(Self::model_base_path()?.join(Path::new(model_file)))
     }
+
+    /// The model's maximum input length in tokens, used to decide when a
+    /// chunk needs `chunk_text`'s sliding-window treatment instead of a
+    /// single `encode` call.
+    pub fn max_sequence_length(&self) -> usize {
+        self.config.model_max_length.unwrap_or(512)
+    }
+
+    /// Tokenizes `text` once, without truncation, then slices the token
+    /// stream into overlapping windows of `window` tokens advancing by
+    /// `window - overlap`, decoding each window back to plain text. Unlike
+    /// `encode`, which truncates anything past its configured max length,
+    /// this is how a document longer than the model's context window stays
+    /// fully retrievable: as several chunks instead of one that silently
+    /// drops everything past the cutoff.
+    pub fn chunk_text(&self, text: &str, window: usize, overlap: usize) -> Result<Vec<String>> {
+        if window == 0 {
+            anyhow::bail!("window must be greater than zero");
+        }
+        if overlap >= window {
+            anyhow::bail!("overlap must be smaller than window");
+        }
+
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow!("tokenization failed: {e}"))?;
+        let ids = encoding.get_ids();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let stride = window - overlap;
+        let mut chunks = Vec::new();
+        let mut start = 0usize;
+
+        loop {
+            let end = (start + window).min(ids.len());
+            let decoded = self
+                .tokenizer
+                .decode(&ids[start..end], true)
+                .map_err(|e| anyhow!("failed to decode chunk: {e}"))?;
+            chunks.push(decoded);
+
+            if end == ids.len() {
+                break;
+            }
+            start += stride;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Cheap token count for a single string, without padding/truncation.
+    /// Used by the chunk splitter to size pieces against a token budget.
+    pub fn count_tokens(&self, text: &str) -> Result<usize> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow!("tokenization failed: {e}"))?;
+        Ok(encoding.get_ids().len())
+    }
 }
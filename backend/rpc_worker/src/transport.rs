@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, Read, Write};
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+
+/// Which message framing the stdio pipe is speaking. `LineDelimited` is
+/// LexAI's original newline-per-message protocol; `ContentLength` is the
+/// `Content-Length: N\r\n\r\n<N bytes>` framing used by LSP-style editor
+/// clients, which tolerates message bodies containing raw newlines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportMode {
+    LineDelimited,
+    ContentLength,
+}
+
+pub struct Transport<R> {
+    reader: R,
+    mode: TransportMode,
+}
+
+impl<R: BufRead> Transport<R> {
+    pub fn new(reader: R, mode: TransportMode) -> Self {
+        Self { reader, mode }
+    }
+
+    /// Peeks the first bytes of `reader` to decide the framing without
+    /// consuming them, so callers that don't know their client's protocol up
+    /// front can just construct a `Transport` and start reading.
+    pub fn sniff(mut reader: R) -> Result<Self> {
+        let mode = {
+            let peeked = reader.fill_buf().context("failed to peek transport input")?;
+            if peeked.starts_with(CONTENT_LENGTH_HEADER.as_bytes()) {
+                TransportMode::ContentLength
+            } else {
+                TransportMode::LineDelimited
+            }
+        };
+        Ok(Self { reader, mode })
+    }
+
+    pub fn mode(&self) -> TransportMode {
+        self.mode
+    }
+
+    /// Reads one full JSON message, returning `Ok(None)` at EOF.
+    pub fn read_message(&mut self) -> Result<Option<String>> {
+        match self.mode {
+            TransportMode::LineDelimited => self.read_line_delimited(),
+            TransportMode::ContentLength => self.read_content_length(),
+        }
+    }
+
+    fn read_line_delimited(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+
+    fn read_content_length(&mut self) -> Result<Option<String>> {
+        let mut content_length: Option<usize> = None;
+        loop {
+            let mut header_line = String::new();
+            let read = self.reader.read_line(&mut header_line)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            let trimmed = header_line.trim_end_matches(['\r', '\n']);
+            if trimmed.is_empty() {
+                break;
+            }
+            if let Some(value) = trimmed.strip_prefix(CONTENT_LENGTH_HEADER) {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .context("invalid Content-Length header")?,
+                );
+            }
+        }
+
+        let length = content_length.context("message missing Content-Length header")?;
+        let mut body = vec![0u8; length];
+        self.reader.read_exact(&mut body)?;
+        Ok(Some(
+            String::from_utf8(body).context("message body is not valid utf-8")?,
+        ))
+    }
+
+    /// Writes one message to `writer` using this transport's framing.
+    pub fn write_message<W: Write>(&self, writer: &mut W, body: &str) -> Result<()> {
+        match self.mode {
+            TransportMode::LineDelimited => {
+                writeln!(writer, "{body}")?;
+            }
+            TransportMode::ContentLength => {
+                write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn line_delimited_reads_one_message_per_line() {
+        let input = b"{\"a\":1}\n{\"b\":2}\n".as_slice();
+        let mut transport = Transport::new(BufReader::new(input), TransportMode::LineDelimited);
+        assert_eq!(
+            transport.read_message().unwrap(),
+            Some("{\"a\":1}".to_string())
+        );
+        assert_eq!(
+            transport.read_message().unwrap(),
+            Some("{\"b\":2}".to_string())
+        );
+        assert_eq!(transport.read_message().unwrap(), None);
+    }
+
+    #[test]
+    fn content_length_reads_framed_body_with_embedded_newlines() {
+        let body = "{\n\"text\":\"line one\\nline two\"\n}";
+        let framed = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let mut transport = Transport::new(
+            BufReader::new(framed.as_bytes()),
+            TransportMode::ContentLength,
+        );
+        assert_eq!(transport.read_message().unwrap(), Some(body.to_string()));
+        assert_eq!(transport.read_message().unwrap(), None);
+    }
+
+    #[test]
+    fn sniff_detects_content_length_mode() {
+        let framed = b"Content-Length: 2\r\n\r\n{}".as_slice();
+        let transport = Transport::sniff(BufReader::new(framed)).unwrap();
+        assert_eq!(transport.mode(), TransportMode::ContentLength);
+    }
+
+    #[test]
+    fn sniff_detects_line_delimited_mode() {
+        let input = b"{\"jsonrpc\":\"2.0\"}\n".as_slice();
+        let transport = Transport::sniff(BufReader::new(input)).unwrap();
+        assert_eq!(transport.mode(), TransportMode::LineDelimited);
+    }
+
+    #[test]
+    fn write_message_frames_according_to_mode() {
+        let transport = Transport::new(BufReader::new([].as_slice()), TransportMode::ContentLength);
+        let mut out = Vec::new();
+        transport.write_message(&mut out, "{}").unwrap();
+        assert_eq!(out, b"Content-Length: 2\r\n\r\n{}");
+
+        let transport = Transport::new(BufReader::new([].as_slice()), TransportMode::LineDelimited);
+        let mut out = Vec::new();
+        transport.write_message(&mut out, "{}").unwrap();
+        assert_eq!(out, b"{}\n");
+    }
+}
@@ -1,40 +1,42 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     error::Error,
     fs,
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, OnceLock,
     },
 };
 
 use chrono::Utc;
 
+use argon2::Argon2;
 use blake3::hash;
-use genanki_rs::{basic_model, Deck, Error as AnkiError, Note};
-use genpdf::{
-    elements::{Break, Paragraph, StyledElement},
-    fonts::{FontData, FontFamily},
-    style::Effect,
-    Document,
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
 };
+use hmac::{Hmac, Mac};
+use lexai_core::Term;
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
+use sha2::{Digest, Sha256};
+use reqwest::Client as HttpClient;
+use sysinfo::{Pid, System};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
-use sqlx::{
-    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
-    ConnectOptions, Row, SqlitePool,
-};
+use sqlx::{Row, SqlitePool};
 use tauri::async_runtime::spawn_blocking;
 use tauri::{Emitter, Manager, State, WindowEvent};
 use tauri_plugin_dialog::{DialogExt, FilePath};
 use tauri_plugin_store::StoreExt;
 use tauri_plugin_stronghold::stronghold::Stronghold;
+use uuid::Uuid;
 use tokio::{
     fs as tokio_fs,
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    process::{Child, ChildStderr, ChildStdin, ChildStdout},
-    sync::{oneshot, Mutex as AsyncMutex},
+    process::{Child, ChildStderr, ChildStdin},
+    sync::{oneshot, Mutex as AsyncMutex, Semaphore},
     time::{timeout, Duration},
 };
 
@@ -47,15 +49,142 @@ struct RpcDiagnostics {
     stderr_tail: Option<String>,
 }
 
+/// Process-level view of the spawned RPC worker, resolved by matching its
+/// PID against `sysinfo`'s process table and `netstat2`'s socket table. Lets
+/// the diagnostics panel tell a wedged worker (running, high CPU, port
+/// unbound) apart from a crashed one ([`RpcDiagnostics::running`] is false)
+/// or a merely slow one (running, idle, port bound).
+#[derive(Serialize)]
+struct BackendDiagnostics {
+    pid: u32,
+    cpu: f32,
+    mem_bytes: u64,
+    listening_ports: Vec<u16>,
+    uptime_secs: u64,
+}
+
+/// Structured failure from an RPC call, kept distinct from a plain `String`
+/// so the frontend can branch on *why* a call failed (retry a timeout,
+/// prompt for a key on an auth `Protocol` error, show diagnostics on
+/// `WorkerExited`) instead of pattern-matching substrings of a message.
+/// Serializes as a tagged object (`{"kind": "Timeout"}`,
+/// `{"kind": "Protocol", "code": -32001, "message": "...", "data": ...}`, ...)
+/// so it survives the `#[tauri::command]` boundary without being flattened.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+enum RpcError {
+    Timeout,
+    WorkerExited {
+        status: Option<i32>,
+        stderr_tail: Option<String>,
+    },
+    Protocol {
+        code: i64,
+        message: String,
+        data: Option<JsonValue>,
+    },
+    Transport {
+        message: String,
+    },
+    Decode {
+        message: String,
+    },
+}
+
+impl RpcError {
+    fn transport(message: impl Into<String>) -> Self {
+        RpcError::Transport {
+            message: message.into(),
+        }
+    }
+
+    fn decode(message: impl Into<String>) -> Self {
+        RpcError::Decode {
+            message: message.into(),
+        }
+    }
+
+    /// Parses a JSON-RPC `error` object into a `Protocol` variant, preserving
+    /// the `code` and `data` fields instead of stringifying the whole thing.
+    fn from_json_rpc_error(error: &JsonValue) -> Self {
+        RpcError::Protocol {
+            code: error.get("code").and_then(JsonValue::as_i64).unwrap_or(0),
+            message: error
+                .get("message")
+                .and_then(JsonValue::as_str)
+                .unwrap_or("Unknown RPC error")
+                .to_string(),
+            data: error.get("data").cloned(),
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Timeout => write!(f, "Timed out waiting for RPC response"),
+            RpcError::WorkerExited { status, .. } => match status {
+                Some(code) => write!(f, "RPC worker exited unexpectedly with status {code}"),
+                None => write!(f, "RPC worker exited unexpectedly"),
+            },
+            RpcError::Protocol { code, message, .. } => write!(f, "{message} (code {code})"),
+            RpcError::Transport { message } => write!(f, "{message}"),
+            RpcError::Decode { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Lets call sites that haven't been converted to surface the structured
+/// error still propagate it with `?` into a plain `Result<_, String>`.
+impl From<RpcError> for String {
+    fn from(err: RpcError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Pending in-flight calls keyed by JSON-RPC request id, so the background
+/// reader task can route each response line back to the caller awaiting it.
+type PendingMap = Arc<AsyncMutex<HashMap<u64, oneshot::Sender<Result<JsonValue, RpcError>>>>>;
+
+/// Protocol version this client build speaks. Only the major component is
+/// checked against the worker's reported version during the `initialize`
+/// handshake; minor bumps are additive and assumed compatible.
+const CLIENT_PROTOCOL_VERSION: &str = "1.0";
+
+/// Capabilities negotiated with the running RPC worker during its
+/// `initialize` handshake, so the frontend can hide UI for features the
+/// bundled worker build does not support.
+#[derive(Clone, Serialize)]
+struct WorkerCapabilities {
+    protocol_version: String,
+    embeddings: bool,
+    rerank: bool,
+    ocr: bool,
+}
+
+impl WorkerCapabilities {
+    fn unknown() -> Self {
+        Self {
+            protocol_version: "0.0".to_string(),
+            embeddings: false,
+            rerank: false,
+            ocr: false,
+        }
+    }
+}
+
 struct RpcClient {
     child: Arc<AsyncMutex<Child>>,
     stdin: Arc<AsyncMutex<ChildStdin>>,
-    stdout: Arc<AsyncMutex<BufReader<ChildStdout>>>,
+    pending: PendingMap,
     stderr: Option<Arc<AsyncMutex<BufReader<ChildStderr>>>>,
     stderr_buf: Arc<AsyncMutex<Vec<String>>>,
     log_file: Option<Arc<AsyncMutex<tokio_fs::File>>>,
     next_id: AtomicU64,
     response_timeout: Duration,
+    capabilities: WorkerCapabilities,
 }
 
 impl RpcClient {
@@ -63,15 +192,15 @@ impl RpcClient {
         mut child: Child,
         response_timeout: Duration,
         log_path: Option<PathBuf>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, RpcError> {
         let stdin = child
             .stdin
             .take()
-            .ok_or_else(|| "Child process stdin unavailable".to_string())?;
+            .ok_or_else(|| RpcError::transport("Child process stdin unavailable"))?;
         let stdout = child
             .stdout
             .take()
-            .ok_or_else(|| "Child process stdout unavailable".to_string())?;
+            .ok_or_else(|| RpcError::transport("Child process stdout unavailable"))?;
         let stderr = child.stderr.take();
 
         let log_file = if let Some(path) = log_path {
@@ -88,15 +217,16 @@ impl RpcClient {
             None
         };
 
-        let client = Self {
+        let mut client = Self {
             child: Arc::new(AsyncMutex::new(child)),
             stdin: Arc::new(AsyncMutex::new(stdin)),
-            stdout: Arc::new(AsyncMutex::new(BufReader::new(stdout))),
+            pending: Arc::new(AsyncMutex::new(HashMap::new())),
             stderr: stderr.map(|s| Arc::new(AsyncMutex::new(BufReader::new(s)))),
             stderr_buf: Arc::new(AsyncMutex::new(Vec::with_capacity(64))),
             log_file,
             next_id: AtomicU64::new(1),
             response_timeout,
+            capabilities: WorkerCapabilities::unknown(),
         };
 
         if let Some(stderr_reader) = &client.stderr {
@@ -132,19 +262,109 @@ impl RpcClient {
             });
         }
 
+        let pending = client.pending.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut reader = BufReader::new(stdout);
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line).await {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        let Ok(response) = serde_json::from_str::<JsonValue>(&line) else {
+                            continue;
+                        };
+                        let Some(id) = response.get("id").and_then(JsonValue::as_u64) else {
+                            continue;
+                        };
+                        let sender = { pending.lock().await.remove(&id) };
+                        if let Some(sender) = sender {
+                            let outcome = if let Some(error) = response.get("error") {
+                                Err(RpcError::from_json_rpc_error(error))
+                            } else {
+                                response
+                                    .get("result")
+                                    .cloned()
+                                    .ok_or_else(|| RpcError::decode("Response missing result"))
+                            };
+                            let _ = sender.send(outcome);
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            let mut pending = pending.lock().await;
+            for (_, sender) in pending.drain() {
+                let _ = sender.send(Err(RpcError::WorkerExited {
+                    status: None,
+                    stderr_tail: None,
+                }));
+            }
+        });
+
+        client.capabilities = client.negotiate_capabilities().await?;
+
         Ok(client)
     }
 
-    async fn call(&self, method: &str, params: JsonValue) -> Result<JsonValue, String> {
+    /// Performs the `initialize` handshake, refusing to proceed if the
+    /// worker's major protocol version does not match this client build.
+    async fn negotiate_capabilities(&self) -> Result<WorkerCapabilities, RpcError> {
+        let response = self
+            .call(
+                "initialize",
+                json!({ "protocol_version": CLIENT_PROTOCOL_VERSION }),
+            )
+            .await
+            .map_err(|err| RpcError::decode(format!("RPC worker handshake failed: {err}")))?;
+
+        let worker_version = response
+            .get("protocol_version")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| RpcError::decode("Worker handshake response missing protocol_version"))?
+            .to_string();
+
+        let client_major = CLIENT_PROTOCOL_VERSION.split('.').next().unwrap_or_default();
+        let worker_major = worker_version.split('.').next().unwrap_or_default();
+        if client_major != worker_major {
+            return Err(RpcError::decode(format!(
+                "Incompatible RPC worker protocol version: client supports {CLIENT_PROTOCOL_VERSION}.x, worker reported {worker_version}"
+            )));
+        }
+
+        let capabilities = response.get("capabilities").cloned().unwrap_or_default();
+        Ok(WorkerCapabilities {
+            protocol_version: worker_version,
+            embeddings: capabilities
+                .get("embeddings")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(false),
+            rerank: capabilities
+                .get("rerank")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(false),
+            ocr: capabilities
+                .get("ocr")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(false),
+        })
+    }
+
+    async fn call(&self, method: &str, params: JsonValue) -> Result<JsonValue, RpcError> {
         {
             let mut child = self.child.lock().await;
             if let Some(status) = child
                 .try_wait()
-                .map_err(|err| format!("Failed to poll child status: {err}"))?
+                .map_err(|err| RpcError::transport(format!("Failed to poll child status: {err}")))?
             {
-                return Err(format!(
-                    "RPC worker exited unexpectedly with status {status}"
-                ));
+                return Err(RpcError::WorkerExited {
+                    status: status.code(),
+                    stderr_tail: self.stderr_tail().await,
+                });
             }
         }
 
@@ -159,44 +379,46 @@ impl RpcClient {
         let mut payload = request.to_string();
         payload.push('\n');
 
+        let (tx, rx) = oneshot::channel();
         {
-            let mut stdin = self.stdin.lock().await;
-            stdin
-                .write_all(payload.as_bytes())
-                .await
-                .map_err(|err| format!("Failed to write request: {err}"))?;
-            stdin
-                .flush()
-                .await
-                .map_err(|err| format!("Failed to flush request: {err}"))?;
+            let mut pending = self.pending.lock().await;
+            pending.insert(request_id, tx);
         }
 
-        let mut line = String::new();
         {
-            let mut stdout = self.stdout.lock().await;
-            timeout(self.response_timeout, stdout.read_line(&mut line))
-                .await
-                .map_err(|_| "Timed out waiting for RPC response".to_string())?
-                .map_err(|err| format!("Failed to read response: {err}"))?;
+            let mut stdin = self.stdin.lock().await;
+            if let Err(err) = stdin.write_all(payload.as_bytes()).await {
+                self.pending.lock().await.remove(&request_id);
+                return Err(RpcError::transport(format!("Failed to write request: {err}")));
+            }
+            if let Err(err) = stdin.flush().await {
+                self.pending.lock().await.remove(&request_id);
+                return Err(RpcError::transport(format!("Failed to flush request: {err}")));
+            }
         }
 
-        if line.trim().is_empty() {
-            return Err("Received empty response from RPC worker".to_string());
+        match timeout(self.response_timeout, rx).await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(_)) => Err(RpcError::WorkerExited {
+                status: None,
+                stderr_tail: self.stderr_tail().await,
+            }),
+            Err(_) => {
+                self.pending.lock().await.remove(&request_id);
+                Err(RpcError::Timeout)
+            }
         }
+    }
 
-        let response: JsonValue = serde_json::from_str(&line)
-            .map_err(|err| format!("Failed to parse response JSON: {err}"))?;
-
-        if let Some(error) = response.get("error") {
-            return Err(error.to_string());
+    /// Snapshot of the most recent stderr lines, if any, for attaching to a
+    /// `WorkerExited` error.
+    async fn stderr_tail(&self) -> Option<String> {
+        let buf = self.stderr_buf.lock().await;
+        if buf.is_empty() {
+            None
+        } else {
+            Some(buf.join("\n"))
         }
-
-        let result = response
-            .get("result")
-            .cloned()
-            .ok_or_else(|| "Response missing result".to_string())?;
-
-        Ok(result)
     }
 }
 
@@ -210,14 +432,7 @@ impl RpcClient {
                 exit_status = status.code();
             }
         }
-        let stderr_tail = {
-            let buf = self.stderr_buf.lock().await;
-            if buf.is_empty() {
-                None
-            } else {
-                Some(buf.join("\n"))
-            }
-        };
+        let stderr_tail = self.stderr_tail().await;
         RpcDiagnostics {
             running,
             exit_status,
@@ -226,13 +441,36 @@ impl RpcClient {
     }
 }
 
+/// Holds the active profile's database pool behind a lock so
+/// `switch_profile` can re-point every command at a different `lexai.db`
+/// without tearing down and re-registering Tauri state.
 struct AppState {
-    pool: SqlitePool,
+    pool: Arc<AsyncMutex<SqlitePool>>,
+}
+
+impl AppState {
+    fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool: Arc::new(AsyncMutex::new(pool)),
+        }
+    }
+
+    async fn pool(&self) -> SqlitePool {
+        self.pool.lock().await.clone()
+    }
+
+    async fn set_pool(&self, pool: SqlitePool) {
+        *self.pool.lock().await = pool;
+    }
 }
 
 const STRONGHOLD_SNAPSHOT: &str = "stronghold.scout";
+const STRONGHOLD_SALT_FILE: &str = "stronghold.salt";
 const STRONGHOLD_CLIENT_PATH: &[u8] = b"lexai_api_credentials";
 const STRONGHOLD_STORE_PREFIX: &str = "provider::";
+const STRONGHOLD_VERIFIER_KEY: &[u8] = b"lexai_passphrase_verifier";
+const STRONGHOLD_VERIFIER_VALUE: &[u8] = b"lexai-vault-unlocked";
+const STRONGHOLD_PROVIDER_INDEX_KEY: &[u8] = b"lexai_provider_index";
 
 struct StrongholdInner {
     stronghold: Stronghold,
@@ -261,10 +499,102 @@ impl StrongholdInner {
     fn provider_key(provider: &str) -> Vec<u8> {
         format!("{STRONGHOLD_STORE_PREFIX}{provider}").into_bytes()
     }
+
+    /// Writes the known-plaintext verifier record used to detect a wrong
+    /// passphrase without first corrupting or overwriting real secrets.
+    fn write_verifier(&self, client: &StrongholdClient) -> Result<(), String> {
+        client
+            .store()
+            .insert(
+                STRONGHOLD_VERIFIER_KEY.to_vec(),
+                STRONGHOLD_VERIFIER_VALUE.to_vec(),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        self.stronghold.save().map_err(|err| err.to_string())
+    }
+
+    /// `Ok(true)` once a verifier is present and matches, `Ok(false)` if a
+    /// verifier is present but doesn't match (wrong passphrase).
+    fn check_verifier(&self, client: &StrongholdClient) -> Result<bool, String> {
+        match client
+            .store()
+            .get(STRONGHOLD_VERIFIER_KEY)
+            .map_err(|err| err.to_string())?
+        {
+            Some(value) => Ok(value == STRONGHOLD_VERIFIER_VALUE),
+            None => Ok(true),
+        }
+    }
+
+    /// Names of every provider that has ever had a secret saved, so
+    /// `change_master_passphrase` can migrate them one by one without
+    /// relying on the stronghold store exposing key enumeration.
+    fn provider_index(&self, client: &StrongholdClient) -> Result<Vec<String>, String> {
+        match client
+            .store()
+            .get(STRONGHOLD_PROVIDER_INDEX_KEY)
+            .map_err(|err| err.to_string())?
+        {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes).unwrap_or_default()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn record_provider_in_index(&self, client: &StrongholdClient, provider: &str) -> Result<(), String> {
+        let mut providers = self.provider_index(client)?;
+        if !providers.iter().any(|p| p == provider) {
+            providers.push(provider.to_string());
+            let bytes = serde_json::to_vec(&providers).map_err(|err| err.to_string())?;
+            client
+                .store()
+                .insert(STRONGHOLD_PROVIDER_INDEX_KEY.to_vec(), bytes, None)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Derives a Stronghold snapshot key from a user-chosen passphrase via
+/// Argon2id. `salt` is not secret and is kept alongside the snapshot in
+/// `STRONGHOLD_SALT_FILE`.
+fn derive_master_key(passphrase: &str, salt: &[u8]) -> Result<Vec<u8>, String> {
+    let mut key = vec![0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| err.to_string())?;
+    Ok(key)
+}
+
+fn load_or_create_salt(salt_path: &Path) -> Result<Vec<u8>, String> {
+    if salt_path.exists() {
+        fs::read(salt_path).map_err(|err| err.to_string())
+    } else {
+        let mut salt = vec![0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        fs::write(salt_path, &salt).map_err(|err| err.to_string())?;
+        Ok(salt)
+    }
+}
+
+/// The secrets vault starts `Locked` on every launch; nothing is read from
+/// or written to Stronghold until `unlock` succeeds with the user's master
+/// passphrase, at which point it moves to `Unlocked` for the rest of the
+/// session.
+enum VaultState {
+    Locked,
+    Unlocked(StrongholdInner),
 }
 
+/// One Stronghold snapshot backs every profile; each profile is just a
+/// distinct client namespace (`client_path`) within it, so switching
+/// profiles never requires re-deriving the master key or touching the
+/// snapshot file.
 struct SecretsManager {
-    inner: Arc<AsyncMutex<StrongholdInner>>,
+    state: Arc<AsyncMutex<VaultState>>,
+    stronghold_path: PathBuf,
+    salt_path: PathBuf,
+    active_client_path: Arc<AsyncMutex<Vec<u8>>>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -279,6 +609,10 @@ pub struct BatchProgress {
 #[allow(dead_code)]
 const EVT_BATCH_PROGRESS: &str = "batch://progress";
 
+/// Caps how many files are embedded/uploaded concurrently during a batch so
+/// a large drop doesn't flood the worker with simultaneous ONNX/Qdrant calls.
+const MAX_BATCH_CONCURRENCY: usize = 4;
+
 #[tauri::command]
 #[allow(dead_code)]
 async fn start_batch_upload(
@@ -286,77 +620,95 @@ async fn start_batch_upload(
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
     batch_state: State<'_, BatchState>,
+    app_state: State<'_, AppState>,
 ) -> Result<bool, String> {
-    let client = rpc_manager.ensure_client(&app).await?;
+    rpc_manager.ensure_client(&app).await?;
     batch_state
         .cancel
         .store(false, std::sync::atomic::Ordering::Relaxed);
     let total = files.len();
     let per_file: Arc<AsyncMutex<HashMap<String, String>>> =
         Arc::new(AsyncMutex::new(HashMap::new()));
+    let pool = app_state.pool().await;
+    let rpc_manager = rpc_manager.inner().clone();
 
     let app_handle = app.clone();
     tauri::async_runtime::spawn({
         let per_file = per_file.clone();
         let cancel = batch_state.cancel.clone();
         async move {
-            let mut completed = 0usize;
-            let mut failed = 0usize;
+            let semaphore = Arc::new(Semaphore::new(MAX_BATCH_CONCURRENCY));
+            let completed = Arc::new(AtomicUsize::new(0));
+            let failed = Arc::new(AtomicUsize::new(0));
+            let mut in_flight = tokio::task::JoinSet::new();
+
             for spec in files {
                 if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                    break;
+                }
+                // Bounds how many files are embedded/uploaded at once; acquiring
+                // before spawning (rather than inside the task) means we never
+                // spawn more concurrent uploads than permits allow.
+                let permit = semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("batch upload semaphore never closes");
+                let pool = pool.clone();
+                let rpc_manager = rpc_manager.clone();
+                let per_file = per_file.clone();
+                let completed = completed.clone();
+                let failed = failed.clone();
+                let app_handle = app_handle.clone();
+                in_flight.spawn(async move {
+                    let _permit = permit;
+                    let name = spec.file_name.clone();
+                    let result = upload_with_dedup(
+                        &pool,
+                        &rpc_manager,
+                        &app_handle,
+                        &spec.file_path,
+                        &spec.file_name,
+                    )
+                    .await;
+                    {
+                        let mut pf = per_file.lock().await;
+                        match result {
+                            Ok(status) => {
+                                pf.insert(name, status);
+                                completed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            }
+                            Err(e) => {
+                                pf.insert(name, format!("error: {}", e));
+                                failed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            }
+                        }
+                    }
+                    // emit progress as each file finishes
                     let snapshot = {
                         let pf = per_file.lock().await;
                         BatchProgress {
                             total,
-                            completed,
-                            failed,
-                            cancelled: true,
+                            completed: completed.load(std::sync::atomic::Ordering::Relaxed),
+                            failed: failed.load(std::sync::atomic::Ordering::Relaxed),
+                            cancelled: false,
                             per_file: pf.clone(),
                         }
                     };
                     let _ = app_handle.emit(EVT_BATCH_PROGRESS, snapshot);
-                    break;
-                }
-                let name = spec.file_name.clone();
-                let result = client
-                    .call(
-                        "upload_document",
-                        json!({"file_path": spec.file_path, "file_name": spec.file_name}),
-                    )
-                    .await;
-                {
-                    let mut pf = per_file.lock().await;
-                    match result {
-                        Ok(_) => {
-                            pf.insert(name, "ok".into());
-                            completed += 1;
-                        }
-                        Err(e) => {
-                            pf.insert(name, format!("error: {}", e));
-                            failed += 1;
-                        }
-                    }
-                }
-                // emit progress after each file
-                let snapshot = {
-                    let pf = per_file.lock().await;
-                    BatchProgress {
-                        total,
-                        completed,
-                        failed,
-                        cancelled: false,
-                        per_file: pf.clone(),
-                    }
-                };
-                let _ = app_handle.emit(EVT_BATCH_PROGRESS, snapshot);
+                });
             }
-            // final snapshot if finished naturally
+
+            // Wait for whatever already started before a cancellation (or the
+            // last batch of files) to finish before reporting the final state.
+            while in_flight.join_next().await.is_some() {}
+
             let snapshot = {
                 let pf = per_file.lock().await;
                 BatchProgress {
                     total,
-                    completed,
-                    failed,
+                    completed: completed.load(std::sync::atomic::Ordering::Relaxed),
+                    failed: failed.load(std::sync::atomic::Ordering::Relaxed),
                     cancelled: cancel.load(std::sync::atomic::Ordering::Relaxed),
                     per_file: pf.clone(),
                 }
@@ -384,6 +736,106 @@ struct BatchFileSpec {
     file_name: String,
 }
 
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+        .collect()
+}
+
+/// Uploads one file through the RPC worker, skipping the call entirely when
+/// the exact file bytes were already processed (a `known_chunks` row with
+/// `ordinal = -1`), and otherwise forwarding every chunk hash already known
+/// so the worker only embeds and indexes chunks it hasn't seen before. Goes
+/// through `RpcManager::call` rather than a raw `RpcClient` so a worker crash
+/// mid-batch is recovered from instead of failing every remaining file.
+async fn upload_with_dedup(
+    pool: &SqlitePool,
+    rpc_manager: &RpcManager,
+    app: &tauri::AppHandle,
+    file_path: &str,
+    file_name: &str,
+) -> Result<String, String> {
+    let bytes = tokio_fs::read(file_path)
+        .await
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    let file_hash = hash(&bytes).as_bytes().to_vec();
+
+    if let Some(row) = sqlx::query(
+        "SELECT document_id FROM known_chunks WHERE hash = ? AND ordinal = -1",
+    )
+    .bind(&file_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| err.to_string())?
+    {
+        let document_id: String = row.try_get("document_id").map_err(|err| err.to_string())?;
+        return Ok(format!("ok (deduplicated, document_id={document_id})"));
+    }
+
+    let known_rows = sqlx::query("SELECT hash FROM known_chunks WHERE ordinal >= 0")
+        .fetch_all(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+    let known_chunk_hashes: Vec<String> = known_rows
+        .iter()
+        .map(|row| bytes_to_hex(&row.get::<Vec<u8>, _>("hash")))
+        .collect();
+
+    let response = rpc_manager
+        .call(
+            app,
+            "upload_document",
+            json!({
+                "file_path": file_path,
+                "file_name": file_name,
+                "known_chunk_hashes": known_chunk_hashes,
+            }),
+        )
+        .await?;
+
+    let document_id = response
+        .get("document_id")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| "Upload response missing document_id".to_string())?
+        .to_string();
+
+    let chunk_hashes: Vec<String> = response
+        .get("chunk_hashes")
+        .and_then(JsonValue::as_array)
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
+    for (ordinal, hash_hex) in chunk_hashes.iter().enumerate() {
+        sqlx::query("INSERT OR IGNORE INTO known_chunks (hash, document_id, ordinal) VALUES (?, ?, ?)")
+            .bind(hex_to_bytes(hash_hex))
+            .bind(&document_id)
+            .bind(ordinal as i64)
+            .execute(pool)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO known_chunks (hash, document_id, ordinal) VALUES (?, ?, -1)")
+        .bind(&file_hash)
+        .bind(&document_id)
+        .execute(pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let embedded = response
+        .get("embedded_chunk_count")
+        .and_then(JsonValue::as_u64)
+        .unwrap_or(chunk_hashes.len() as u64);
+    Ok(format!(
+        "ok (embedded {embedded}/{} chunks)",
+        chunk_hashes.len()
+    ))
+}
+
 #[derive(Clone, Default)]
 #[allow(dead_code)]
 struct BatchState {
@@ -391,16 +843,201 @@ struct BatchState {
     cancel: Arc<std::sync::atomic::AtomicBool>,
 }
 
+const VAULT_LOCKED_ERROR: &str =
+    "Vault is locked. Unlock it with your master passphrase first.";
+
 impl SecretsManager {
-    fn new(inner: StrongholdInner) -> Self {
+    fn locked(stronghold_path: PathBuf, salt_path: PathBuf, client_path: Vec<u8>) -> Self {
+        Self {
+            state: Arc::new(AsyncMutex::new(VaultState::Locked)),
+            stronghold_path,
+            salt_path,
+            active_client_path: Arc::new(AsyncMutex::new(client_path)),
+        }
+    }
+
+    #[cfg(test)]
+    fn from_unlocked(inner: StrongholdInner) -> Self {
+        let client_path = inner.client_path.clone();
         Self {
-            inner: Arc::new(AsyncMutex::new(inner)),
+            state: Arc::new(AsyncMutex::new(VaultState::Unlocked(inner))),
+            stronghold_path: PathBuf::new(),
+            salt_path: PathBuf::new(),
+            active_client_path: Arc::new(AsyncMutex::new(client_path)),
+        }
+    }
+
+    async fn is_unlocked(&self) -> bool {
+        matches!(*self.state.lock().await, VaultState::Unlocked(_))
+    }
+
+    async fn active_client_path(&self) -> Vec<u8> {
+        self.active_client_path.lock().await.clone()
+    }
+
+    /// Re-points the vault at a different profile's client namespace. If
+    /// already unlocked, the swap takes effect immediately; otherwise it's
+    /// picked up the next time `unlock` runs.
+    async fn switch_profile(&self, client_path: Vec<u8>) {
+        *self.active_client_path.lock().await = client_path.clone();
+        if let VaultState::Unlocked(inner) = &mut *self.state.lock().await {
+            inner.client_path = client_path;
+        }
+    }
+
+    /// Opens the snapshot with a key derived from `passphrase`, checking the
+    /// stored verifier record (or creating one, on first unlock) so a wrong
+    /// passphrase fails with a clear error instead of a corrupted vault.
+    async fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        let mut guard = self.state.lock().await;
+        if matches!(*guard, VaultState::Unlocked(_)) {
+            return Ok(());
+        }
+
+        let salt = load_or_create_salt(&self.salt_path)?;
+        let master_key = derive_master_key(passphrase, &salt)?;
+        let stronghold = Stronghold::new(&self.stronghold_path, master_key)
+            .map_err(|err| err.to_string())?;
+        let inner = StrongholdInner {
+            stronghold,
+            client_path: self.active_client_path().await,
+        };
+
+        let client = inner.ensure_client()?;
+        if !inner.check_verifier(&client)? {
+            return Err("Incorrect passphrase.".to_string());
+        }
+        inner.write_verifier(&client)?;
+
+        *guard = VaultState::Unlocked(inner);
+        Ok(())
+    }
+
+    /// Re-encrypts the vault under a new passphrase: opens the current
+    /// snapshot with `old_passphrase`, writes every known secret for every
+    /// profile in `client_paths` into a fresh snapshot keyed by
+    /// `new_passphrase` at a temp path, then atomically renames it over the
+    /// original so a crash mid-rekey leaves either the old snapshot or the
+    /// fully-written new one, never a half-written file. `client_paths` must
+    /// cover every existing profile, not just the active one, or rekeying
+    /// would silently drop the other profiles' secrets.
+    async fn rekey(
+        &self,
+        old_passphrase: &str,
+        new_passphrase: &str,
+        client_paths: &[Vec<u8>],
+    ) -> Result<(), String> {
+        let mut guard = self.state.lock().await;
+
+        let old_salt = load_or_create_salt(&self.salt_path)?;
+        let old_master_key = derive_master_key(old_passphrase, &old_salt)?;
+        let old_stronghold = Stronghold::new(&self.stronghold_path, old_master_key.clone())
+            .map_err(|err| err.to_string())?;
+
+        let active_client_path = self.active_client_path().await;
+        let old_inner = StrongholdInner {
+            stronghold: old_stronghold,
+            client_path: active_client_path.clone(),
+        };
+        let old_client = old_inner.ensure_client()?;
+        if !old_inner.check_verifier(&old_client)? {
+            return Err("Incorrect current passphrase.".to_string());
+        }
+
+        let mut profile_secrets = Vec::new();
+        for client_path in client_paths {
+            let old_profile_inner = StrongholdInner {
+                stronghold: Stronghold::new(&self.stronghold_path, old_master_key.clone())
+                    .map_err(|err| err.to_string())?,
+                client_path: client_path.clone(),
+            };
+            let old_profile_client = old_profile_inner.ensure_client()?;
+
+            let providers = old_profile_inner.provider_index(&old_profile_client)?;
+            let mut secrets = Vec::new();
+            for provider in &providers {
+                let record_key = StrongholdInner::provider_key(provider);
+                if let Some(value) = old_profile_client
+                    .store()
+                    .get(&record_key)
+                    .map_err(|err| err.to_string())?
+                {
+                    secrets.push((provider.clone(), value));
+                }
+            }
+            profile_secrets.push((client_path.clone(), secrets));
+        }
+
+        let new_snapshot_path = self.stronghold_path.with_extension("scout.rekey");
+        // A prior rekey that failed or was interrupted before the final
+        // rename can leave this file behind; a fresh `Stronghold::new` below
+        // would very likely fail to open it, permanently blocking every
+        // future rekey attempt. Clear it before we start.
+        let _ = fs::remove_file(&new_snapshot_path);
+
+        let new_salt = {
+            let mut salt = vec![0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            salt
+        };
+        let new_master_key = derive_master_key(new_passphrase, &new_salt)?;
+
+        let build_result: Result<(), String> = (|| {
+            for (client_path, secrets) in &profile_secrets {
+                let new_stronghold = Stronghold::new(&new_snapshot_path, new_master_key.clone())
+                    .map_err(|err| err.to_string())?;
+                let new_inner = StrongholdInner {
+                    stronghold: new_stronghold,
+                    client_path: client_path.clone(),
+                };
+                let new_client = new_inner.ensure_client()?;
+
+                for (provider, value) in secrets {
+                    new_client
+                        .store()
+                        .insert(StrongholdInner::provider_key(provider), value.clone(), None)
+                        .map_err(|err| err.to_string())?;
+                    new_inner.record_provider_in_index(&new_client, provider)?;
+                }
+                new_inner.write_verifier(&new_client)?;
+            }
+            Ok(())
+        })();
+
+        // This closure never moves `new_client`/`new_inner` out, so the
+        // snapshot file is never left open by the time we need to remove or
+        // rename it below.
+        if let Err(err) = build_result {
+            let _ = fs::remove_file(&new_snapshot_path);
+            return Err(err);
+        }
+
+        drop(old_client);
+        drop(old_inner);
+
+        if let Err(err) = fs::rename(&new_snapshot_path, &self.stronghold_path) {
+            let _ = fs::remove_file(&new_snapshot_path);
+            return Err(err.to_string());
         }
+        fs::write(&self.salt_path, &new_salt).map_err(|err| err.to_string())?;
+
+        let master_key = derive_master_key(new_passphrase, &new_salt)?;
+        let stronghold = Stronghold::new(&self.stronghold_path, master_key)
+            .map_err(|err| err.to_string())?;
+        *guard = VaultState::Unlocked(StrongholdInner {
+            stronghold,
+            client_path: active_client_path,
+        });
+
+        Ok(())
     }
 
     async fn save_api_key(&self, provider: &str, key: &str) -> Result<(), String> {
-        let guard = self.inner.lock().await;
-        let client = guard.ensure_client()?;
+        let guard = self.state.lock().await;
+        let VaultState::Unlocked(inner) = &*guard else {
+            return Err(VAULT_LOCKED_ERROR.to_string());
+        };
+        let client = inner.ensure_client()?;
         let record_key = StrongholdInner::provider_key(provider);
         let sanitized = key.trim();
 
@@ -414,14 +1051,18 @@ impl SecretsManager {
                 .store()
                 .insert(record_key.clone(), sanitized.as_bytes().to_vec(), None)
                 .map_err(|err| err.to_string())?;
+            inner.record_provider_in_index(&client, provider)?;
         }
 
-        guard.stronghold.save().map_err(|err| err.to_string())
+        inner.stronghold.save().map_err(|err| err.to_string())
     }
 
     async fn save_api_keys_batch(&self, providers: &[String], key: &str) -> Result<(), String> {
-        let guard = self.inner.lock().await;
-        let client = guard.ensure_client()?;
+        let guard = self.state.lock().await;
+        let VaultState::Unlocked(inner) = &*guard else {
+            return Err(VAULT_LOCKED_ERROR.to_string());
+        };
+        let client = inner.ensure_client()?;
         let sanitized = key.trim();
 
         for provider in providers {
@@ -436,16 +1077,20 @@ impl SecretsManager {
                     .store()
                     .insert(record_key, sanitized.as_bytes().to_vec(), None)
                     .map_err(|err| err.to_string())?;
+                inner.record_provider_in_index(&client, provider)?;
             }
         }
 
         // Only save once after all inserts
-        guard.stronghold.save().map_err(|err| err.to_string())
+        inner.stronghold.save().map_err(|err| err.to_string())
     }
 
     async fn get_api_key(&self, provider: &str) -> Result<Option<String>, String> {
-        let guard = self.inner.lock().await;
-        let client = guard.ensure_client()?;
+        let guard = self.state.lock().await;
+        let VaultState::Unlocked(inner) = &*guard else {
+            return Err(VAULT_LOCKED_ERROR.to_string());
+        };
+        let client = inner.ensure_client()?;
         let record_key = StrongholdInner::provider_key(provider);
 
         match client
@@ -467,8 +1112,11 @@ impl SecretsManager {
     }
 
     async fn has_api_key(&self, provider: &str) -> Result<bool, String> {
-        let guard = self.inner.lock().await;
-        let client = guard.ensure_client()?;
+        let guard = self.state.lock().await;
+        let VaultState::Unlocked(inner) = &*guard else {
+            return Err(VAULT_LOCKED_ERROR.to_string());
+        };
+        let client = inner.ensure_client()?;
         let record_key = StrongholdInner::provider_key(provider);
 
         client
@@ -478,16 +1126,6 @@ impl SecretsManager {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
-struct Term {
-    id: i64,
-    term: String,
-    definition: String,
-    definition_cn: Option<String>,
-    review_stage: i64,
-    last_reviewed_at: Option<String>,
-}
-
 #[derive(Debug, Deserialize)]
 struct SearchResultPayload {
     chunk_text: String,
@@ -512,24 +1150,21 @@ struct UploadPayload {
     status: String,
 }
 
-fn escape_csv_cell(value: &str) -> String {
-    let mut escaped = String::with_capacity(value.len() + 2);
-    escaped.push('"');
-    for ch in value.chars() {
-        if ch == '"' {
-            escaped.push('"');
-            escaped.push('"');
-        } else {
-            escaped.push(ch);
-        }
-    }
-    escaped.push('"');
-    escaped
-}
+/// How many times a crashed worker is respawned and the call retried before
+/// the error is surfaced to the caller.
+const MAX_CALL_RETRIES: u32 = 2;
+/// Interval between liveness polls of the cached worker, so a crash is
+/// noticed even between calls rather than only on the next `call`.
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(15);
+const EVT_WORKER_RESTARTED: &str = "rpc://worker-restarted";
 
 #[derive(Clone, Default)]
 struct RpcManager {
     client: Arc<AsyncMutex<Option<Arc<RpcClient>>>>,
+    /// Shared across diagnostics polls so `sysinfo` sees the same process
+    /// snapshot each time: it needs two time-separated refreshes to compute
+    /// a CPU delta, which a fresh `System` per call could never produce.
+    diagnostics_system: Arc<std::sync::Mutex<Option<System>>>,
 }
 
 impl RpcManager {
@@ -537,11 +1172,17 @@ impl RpcManager {
         Self::default()
     }
 
+    fn diagnostics_system_handle(&self) -> Arc<std::sync::Mutex<Option<System>>> {
+        self.diagnostics_system.clone()
+    }
+
     fn client_handle(&self) -> Arc<AsyncMutex<Option<Arc<RpcClient>>>> {
         self.client.clone()
     }
 
-    async fn ensure_client(&self, app: &tauri::AppHandle) -> Result<Arc<RpcClient>, String> {
+    async fn ensure_client(&self, app: &tauri::AppHandle) -> Result<Arc<RpcClient>, RpcError> {
+        self.reap_if_dead(app).await;
+
         if let Some(existing) = self.client.lock().await.as_ref() {
             return Ok(existing.clone());
         }
@@ -551,15 +1192,82 @@ impl RpcManager {
         Ok(client)
     }
 
-    async fn shutdown_with(handle: Arc<AsyncMutex<Option<Arc<RpcClient>>>>) {
-        if let Some(client) = handle.lock().await.take() {
-            let _ = client.child.lock().await.kill().await;
+    /// Issues an RPC call against the supervised worker, transparently
+    /// reaping and respawning it when it has died — either before this call
+    /// started or while it was in flight — and retrying up to
+    /// `MAX_CALL_RETRIES` times with a short backoff.
+    async fn call(
+        &self,
+        app: &tauri::AppHandle,
+        method: &str,
+        params: JsonValue,
+    ) -> Result<JsonValue, RpcError> {
+        let mut attempt = 0;
+        loop {
+            let client = self.ensure_client(app).await?;
+            match client.call(method, params.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(RpcError::WorkerExited { .. }) if attempt < MAX_CALL_RETRIES => {
+                    attempt += 1;
+                    self.reap_dead(app, &client).await;
+                    tokio::time::sleep(Duration::from_millis(200 * u64::from(attempt))).await;
+                }
+                Err(err) => return Err(err),
+            }
         }
     }
-}
 
-async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
-    // In dev mode, check src-tauri/resources first
+    /// Drops `client` from the cache (if it's still the active one), reaps
+    /// its child so a self-terminated worker never lingers as a zombie, and
+    /// emits `rpc://worker-restarted` with its last diagnostics so the UI can
+    /// surface the instability.
+    async fn reap_dead(&self, app: &tauri::AppHandle, client: &Arc<RpcClient>) {
+        let diagnostics = client.diagnostics().await;
+        {
+            let mut guard = self.client.lock().await;
+            if matches!(guard.as_ref(), Some(current) if Arc::ptr_eq(current, client)) {
+                guard.take();
+            }
+        }
+        let _ = client.child.lock().await.wait().await;
+        let _ = app.emit(EVT_WORKER_RESTARTED, diagnostics);
+    }
+
+    /// Checks whether the cached worker has already exited on its own; if so
+    /// reaps it now instead of waiting for the next failed call to notice.
+    async fn reap_if_dead(&self, app: &tauri::AppHandle) {
+        let dead = {
+            let guard = self.client.lock().await;
+            match guard.as_ref() {
+                Some(client) => {
+                    let exited = client
+                        .child
+                        .lock()
+                        .await
+                        .try_wait()
+                        .ok()
+                        .flatten()
+                        .is_some();
+                    exited.then(|| client.clone())
+                }
+                None => None,
+            }
+        };
+
+        if let Some(client) = dead {
+            self.reap_dead(app, &client).await;
+        }
+    }
+
+    async fn shutdown_with(handle: Arc<AsyncMutex<Option<Arc<RpcClient>>>>) {
+        if let Some(client) = handle.lock().await.take() {
+            let _ = client.child.lock().await.kill().await;
+        }
+    }
+}
+
+async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, RpcError> {
+    // In dev mode, check src-tauri/resources first
     let exe_name = if cfg!(windows) {
         "rpc_server.exe"
     } else {
@@ -571,7 +1279,7 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
 
     // 1. Development mode: src-tauri/resources/rpc_server/rpc_server.exe
     if cfg!(debug_assertions) {
-        let current_exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let current_exe = std::env::current_exe().map_err(|e| RpcError::transport(e.to_string()))?;
         if let Some(target_dir) = current_exe.parent().and_then(|p| p.parent()) {
             // Try: target/debug/../resources/rpc_server/rpc_server.exe
             let dev_path = target_dir
@@ -616,7 +1324,7 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
                 "rpc_server/rpc_server",
                 tauri::path::BaseDirectory::Resource,
             )
-            .map_err(|err| err.to_string())?;
+            .map_err(|err| RpcError::transport(err.to_string()))?;
 
         #[cfg(windows)]
         {
@@ -633,7 +1341,7 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
                         "resources/rpc_server/rpc_server.exe",
                         tauri::path::BaseDirectory::Resource,
                     )
-                    .map_err(|err| err.to_string())?;
+                    .map_err(|err| RpcError::transport(err.to_string()))?;
                 if alt.exists() {
                     resource_path = Some(alt);
                 }
@@ -649,15 +1357,15 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
     }
 
     let resource_path = resource_path.ok_or_else(|| {
-        format!(
+        RpcError::transport(format!(
             "RPC worker binary not found. Searched in development and production locations for {}",
             exe_name
-        )
+        ))
     })?;
 
     let resource_dir = resource_path
         .parent()
-        .ok_or_else(|| "Failed to resolve RPC resource directory".to_string())?
+        .ok_or_else(|| RpcError::transport("Failed to resolve RPC resource directory"))?
         .to_path_buf();
 
     // The _internal directory is required on Linux for bundled libs; skip strict check on other OSes
@@ -665,37 +1373,37 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
     {
         let internal_dir = resource_dir.join("_internal");
         if !internal_dir.exists() {
-            return Err(format!(
+            return Err(RpcError::transport(format!(
                 "RPC resource internal directory missing at {}",
                 internal_dir.display()
-            ));
+            )));
         }
     }
 
     let storage_dir = app
         .path()
         .app_data_dir()
-        .map_err(|err| err.to_string())?
+        .map_err(|err| RpcError::transport(err.to_string()))?
         .join("qdrant");
 
-    fs::create_dir_all(&storage_dir).map_err(|err| err.to_string())?;
+    fs::create_dir_all(&storage_dir).map_err(|err| RpcError::transport(err.to_string()))?;
 
     // logs dir
     let logs_dir = app
         .path()
         .app_data_dir()
-        .map_err(|err| err.to_string())?
+        .map_err(|err| RpcError::transport(err.to_string()))?
         .join("logs");
-    fs::create_dir_all(&logs_dir).map_err(|err| err.to_string())?;
+    fs::create_dir_all(&logs_dir).map_err(|err| RpcError::transport(err.to_string()))?;
     let log_path = logs_dir.join("rpc_server.log");
 
     // huggingface cache dir (avoid user home cache path issues on Windows)
     let hf_cache_dir = app
         .path()
         .app_data_dir()
-        .map_err(|err| err.to_string())?
+        .map_err(|err| RpcError::transport(err.to_string()))?
         .join("hf-cache");
-    fs::create_dir_all(&hf_cache_dir).map_err(|err| err.to_string())?;
+    fs::create_dir_all(&hf_cache_dir).map_err(|err| RpcError::transport(err.to_string()))?;
 
     let mut command = tokio::process::Command::new(resource_path);
     command.kill_on_drop(true);
@@ -768,7 +1476,7 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
 
     let child = command
         .spawn()
-        .map_err(|err| format!("Failed to spawn RPC worker: {err}"))?;
+        .map_err(|err| RpcError::transport(format!("Failed to spawn RPC worker: {err}")))?;
     RpcClient::new(child, Duration::from_secs(30), Some(log_path)).await
 }
 
@@ -776,26 +1484,25 @@ async fn spawn_rpc_worker(app: &tauri::AppHandle) -> Result<RpcClient, String> {
 async fn fetch_backend_status(
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
-) -> Result<String, String> {
-    let client = rpc_manager.ensure_client(&app).await?;
+) -> Result<String, RpcError> {
     // Try health first; fall back to ping for older workers
-    match client.call("health", json!({})).await {
+    match rpc_manager.call(&app, "health", json!({})).await {
         Ok(val) => {
             let status = val
                 .get("status")
                 .and_then(JsonValue::as_str)
                 .map(str::to_string)
-                .ok_or_else(|| "Invalid health response".to_string())?;
+                .ok_or_else(|| RpcError::decode("Invalid health response"))?;
             Ok(status)
         }
         Err(_) => {
-            let pong = client
-                .call("ping", json!({}))
+            let pong = rpc_manager
+                .call(&app, "ping", json!({}))
                 .await?
                 .get("status")
                 .and_then(JsonValue::as_str)
                 .map(str::to_string)
-                .ok_or_else(|| "Invalid ping response".to_string())?;
+                .ok_or_else(|| RpcError::decode("Invalid ping response"))?;
             Ok(pong)
         }
     }
@@ -805,17 +1512,13 @@ async fn fetch_backend_status(
 async fn fetch_backend_health(
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
-) -> Result<JsonValue, String> {
-    let client = rpc_manager.ensure_client(&app).await?;
-    match client.call("health", json!({})).await {
+) -> Result<JsonValue, RpcError> {
+    match rpc_manager.call(&app, "health", json!({})).await {
         Ok(val) => Ok(val),
-        Err(err) => {
-            if err.contains("-32601") || err.to_lowercase().contains("method not found") {
-                client.call("ping", json!({})).await
-            } else {
-                Err(err)
-            }
+        Err(RpcError::Protocol { code: -32601, .. }) => {
+            rpc_manager.call(&app, "ping", json!({})).await
         }
+        Err(err) => Err(err),
     }
 }
 
@@ -823,16 +1526,93 @@ async fn fetch_backend_health(
 async fn fetch_backend_diagnostics(
     rpc_manager: State<'_, RpcManager>,
     app: tauri::AppHandle,
-) -> Result<RpcDiagnostics, String> {
+) -> Result<RpcDiagnostics, RpcError> {
     let client = rpc_manager.ensure_client(&app).await?;
     Ok(client.diagnostics().await)
 }
 
+#[tauri::command]
+async fn fetch_backend_resource_diagnostics(
+    rpc_manager: State<'_, RpcManager>,
+    app: tauri::AppHandle,
+) -> Result<BackendDiagnostics, RpcError> {
+    let client = rpc_manager.ensure_client(&app).await?;
+    let pid = client
+        .child
+        .lock()
+        .await
+        .id()
+        .ok_or_else(|| RpcError::transport("RPC worker has already exited"))?;
+
+    let system_handle = rpc_manager.diagnostics_system_handle();
+    spawn_blocking(move || backend_resource_diagnostics(&system_handle, pid))
+        .await
+        .map_err(|err| RpcError::transport(err.to_string()))?
+}
+
+/// Resolves `pid`'s live CPU%, RSS, and uptime via `sysinfo`, and the TCP
+/// ports it has listening sockets bound to via `netstat2`'s socket→PID
+/// table. Runs on a blocking thread: both lookups walk the OS process and
+/// socket tables synchronously. `system` is cached across calls (rather
+/// than a fresh `System::new()` each time) so `sysinfo` has the prior
+/// snapshot it needs to compute a real CPU delta instead of always
+/// reading ~0 on a process's first-ever refresh.
+fn backend_resource_diagnostics(
+    system: &std::sync::Mutex<Option<System>>,
+    pid: u32,
+) -> Result<BackendDiagnostics, RpcError> {
+    let sysinfo_pid = Pid::from_u32(pid);
+    let mut guard = system.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let system = guard.get_or_insert_with(System::new);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sysinfo_pid]), true);
+    let process = system
+        .process(sysinfo_pid)
+        .ok_or_else(|| RpcError::transport("RPC worker process not found"))?;
+
+    let sockets = get_sockets_info(
+        AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6,
+        ProtocolFlags::TCP,
+    )
+    .map_err(|err| RpcError::transport(format!("Failed to read socket table: {err}")))?;
+
+    let mut listening_ports: Vec<u16> = sockets
+        .into_iter()
+        .filter_map(|socket| match socket.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp_info)
+                if tcp_info.state == TcpState::Listen
+                    && socket.associated_pids.contains(&pid) =>
+            {
+                Some(tcp_info.local_port)
+            }
+            _ => None,
+        })
+        .collect();
+    listening_ports.sort_unstable();
+    listening_ports.dedup();
+
+    Ok(BackendDiagnostics {
+        pid,
+        cpu: process.cpu_usage(),
+        mem_bytes: process.memory(),
+        listening_ports,
+        uptime_secs: process.run_time(),
+    })
+}
+
+#[tauri::command]
+async fn fetch_backend_capabilities(
+    app: tauri::AppHandle,
+    rpc_manager: State<'_, RpcManager>,
+) -> Result<WorkerCapabilities, RpcError> {
+    let client = rpc_manager.ensure_client(&app).await?;
+    Ok(client.capabilities.clone())
+}
+
 #[tauri::command]
 async fn restart_backend(
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
-) -> Result<bool, String> {
+) -> Result<bool, RpcError> {
     let handle = rpc_manager.client_handle();
     RpcManager::shutdown_with(handle).await;
     // spawn new one
@@ -849,13 +1629,13 @@ async fn search_term_contexts(
     term: String,
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, RpcError> {
     let doc_id = doc_id.or(docId).or(document_id).ok_or_else(|| {
-        "missing 'doc_id' (accepted keys: doc_id, docId, document_id)".to_string()
+        RpcError::decode("missing 'doc_id' (accepted keys: doc_id, docId, document_id)")
     })?;
-    let client = rpc_manager.ensure_client(&app).await?;
-    let response = client
+    let response = rpc_manager
         .call(
+            &app,
             "search_term_contexts",
             json!({
                 "document_id": doc_id,
@@ -865,8 +1645,8 @@ async fn search_term_contexts(
         )
         .await?;
 
-    let payload: SearchResponsePayload =
-        serde_json::from_value(response).map_err(|err| format!("Invalid RPC response: {err}"))?;
+    let payload: SearchResponsePayload = serde_json::from_value(response)
+        .map_err(|err| RpcError::decode(format!("Invalid RPC response: {err}")))?;
 
     Ok(payload
         .results
@@ -882,20 +1662,22 @@ async fn upload_document(
     file_name: String,
     app: tauri::AppHandle,
     rpc_manager: State<'_, RpcManager>,
-) -> Result<UploadPayload, String> {
+) -> Result<UploadPayload, RpcError> {
     // Ensure the file path is valid UTF-8 (critical for Chinese characters on Windows)
     // Rust strings are already UTF-8, but verify the path exists
     let path_check = std::path::Path::new(&file_path);
     if !path_check.exists() {
-        return Err(format!("File not found before upload: {}", file_path));
+        return Err(RpcError::transport(format!(
+            "File not found before upload: {}",
+            file_path
+        )));
     }
 
-    let client = rpc_manager.ensure_client(&app).await?;
-
     // Send the path as UTF-8 encoded string via JSON-RPC
     // The file_path will be automatically escaped properly by serde_json
-    let response = match client
+    let response = match rpc_manager
         .call(
+            &app,
             "upload_document",
             json!({
                 "file_path": file_path,
@@ -905,32 +1687,30 @@ async fn upload_document(
         .await
     {
         Ok(val) => Ok(val),
-        Err(e) => {
-            if e.contains("Method not found") {
-                client
-                    .call(
-                        "upload",
-                        json!({
-                            "file_path": file_path,
-                            "file_name": file_name,
-                        }),
-                    )
-                    .await
-            } else {
-                Err(e)
-            }
+        Err(RpcError::Protocol { code: -32601, .. }) => {
+            rpc_manager
+                .call(
+                    &app,
+                    "upload",
+                    json!({
+                        "file_path": file_path,
+                        "file_name": file_name,
+                    }),
+                )
+                .await
         }
+        Err(err) => Err(err),
     }?;
 
-    let mut payload: UploadPayload =
-        serde_json::from_value(response).map_err(|err| format!("Invalid RPC response: {err}"))?;
+    let mut payload: UploadPayload = serde_json::from_value(response)
+        .map_err(|err| RpcError::decode(format!("Invalid RPC response: {err}")))?;
 
     if payload.document_id.trim().is_empty() {
-        return Err("Upload failed: missing document_id".to_string());
+        return Err(RpcError::decode("Upload failed: missing document_id"));
     }
 
     if !payload.status.eq_ignore_ascii_case("processed") {
-        return Err(format!("Upload failed: {}", payload.status));
+        return Err(RpcError::decode(format!("Upload failed: {}", payload.status)));
     }
 
     if payload.extracted_text.is_none() {
@@ -1043,39 +1823,203 @@ async fn add_term(
     definition_cn: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    sqlx::query("INSERT INTO terms (term, definition, definition_cn) VALUES (?, ?, ?)")
-        .bind(term)
-        .bind(definition)
-        .bind(definition_cn)
-        .execute(&state.pool)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    Ok(())
+    lexai_core::add_term(&state.pool().await, term, definition, definition_cn).await
 }
 
 #[tauri::command]
 async fn get_all_terms(state: State<'_, AppState>) -> Result<Vec<Term>, String> {
-    let records = sqlx::query(
-        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at FROM terms ORDER BY created_at DESC",
-    )
-        .fetch_all(&state.pool)
-        .await
-        .map_err(|err| err.to_string())?;
+    lexai_core::get_all_terms(&state.pool().await).await
+}
 
-    let terms = records
-        .into_iter()
-        .map(|row| Term {
-            id: row.get("id"),
-            term: row.get("term"),
-            definition: row.get("definition"),
-            definition_cn: row.get("definition_cn"),
-            review_stage: row.get("review_stage"),
-            last_reviewed_at: row.get("last_reviewed_at"),
-        })
-        .collect();
+/// Meilisearch-style typo budget: queries under 4 chars must match exactly,
+/// 4-7 chars tolerate a single edit, and 8+ chars tolerate two.
+fn typo_budget(query_chars: usize) -> usize {
+    match query_chars {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Levenshtein edit distance via the standard Wagner-Fischer DP, short-
+/// circuiting a row as soon as its running minimum already exceeds
+/// `max_distance` — the caller only needs to know distances within budget.
+fn bounded_levenshtein(a: &[char], b: &[char], max_distance: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        let mut row_min = row[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            row_min = row_min.min(row[j]);
+        }
+        if row_min > max_distance {
+            return None;
+        }
+        prev = row;
+    }
+
+    let distance = prev[b.len()];
+    (distance <= max_distance).then_some(distance)
+}
+
+/// Exact edit distance between `a` and `b`. `max(len_a, len_b)` is always an
+/// upper bound on Levenshtein distance, so the bounded DP above never
+/// returns `None` here.
+fn levenshtein_distance(a: &[char], b: &[char]) -> usize {
+    let max_distance = a.len().max(b.len());
+    bounded_levenshtein(a, b, max_distance)
+        .expect("distance never exceeds max(len_a, len_b)")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchClass {
+    Exact,
+    Prefix,
+    Fuzzy,
+}
+
+/// BK-tree over lowercased term text keyed by Levenshtein distance, so a
+/// fuzzy lookup only visits candidates within the query's typo-budget
+/// radius instead of scanning every row in the `terms` table.
+struct BkNode {
+    key: Vec<char>,
+    term: Term,
+    children: HashMap<usize, BkNode>,
+}
+
+impl BkNode {
+    fn leaf(key: Vec<char>, term: Term) -> Self {
+        Self {
+            key,
+            term,
+            children: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, key: Vec<char>, term: Term) {
+        let distance = levenshtein_distance(&self.key, &key);
+        if distance == 0 {
+            return; // duplicate term text; keep the first occurrence indexed
+        }
+        match self.children.get_mut(&distance) {
+            Some(child) => child.insert(key, term),
+            None => {
+                self.children.insert(distance, BkNode::leaf(key, term));
+            }
+        }
+    }
+
+    /// Appends every node within `budget` edits of `query` to `out`, using
+    /// the triangle inequality to skip whole subtrees that can't contain a
+    /// match.
+    fn collect_within(&self, query: &[char], budget: usize, out: &mut Vec<(usize, Term)>) {
+        let distance = levenshtein_distance(&self.key, query);
+        if distance <= budget {
+            out.push((distance, self.term.clone()));
+        }
+        let lo = distance.saturating_sub(budget);
+        let hi = distance + budget;
+        for (&edge, child) in &self.children {
+            if edge >= lo && edge <= hi {
+                child.collect_within(query, budget, out);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct BkTree {
+    root: Option<Box<BkNode>>,
+}
+
+impl BkTree {
+    fn build(terms: &[Term]) -> Self {
+        let mut tree = Self::default();
+        for term in terms {
+            tree.insert(term.clone());
+        }
+        tree
+    }
+
+    fn insert(&mut self, term: Term) {
+        let key: Vec<char> = term.term.to_lowercase().chars().collect();
+        match &mut self.root {
+            Some(root) => root.insert(key, term),
+            None => self.root = Some(Box::new(BkNode::leaf(key, term))),
+        }
+    }
+
+    fn fuzzy_matches(&self, query: &[char], budget: usize) -> Vec<(usize, Term)> {
+        let mut out = Vec::new();
+        if let Some(root) = &self.root {
+            root.collect_within(query, budget, &mut out);
+        }
+        out
+    }
+}
+
+/// Ranked typo-tolerant term lookup: exact matches rank first, then prefix
+/// matches, then fuzzy matches within the Meilisearch-style typo budget
+/// (see `typo_budget`), each class sorted by edit distance and then
+/// alphabetically. Used by `search_terms` in place of `find_term_by_name`'s
+/// exact-match query when the caller wants a ranked list of close matches.
+fn fuzzy_search_terms(terms: &[Term], query: &str, limit: usize) -> Vec<Term> {
+    let query_lower = query.trim().to_lowercase();
+    let query_chars: Vec<char> = query_lower.chars().collect();
+    if query_chars.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranked: Vec<(MatchClass, usize, Term)> = Vec::new();
+    let mut seen: HashSet<i64> = HashSet::new();
+
+    for term in terms {
+        let candidate = term.term.to_lowercase();
+        if candidate == query_lower {
+            ranked.push((MatchClass::Exact, 0, term.clone()));
+            seen.insert(term.id);
+        } else if candidate.starts_with(&query_lower) {
+            ranked.push((
+                MatchClass::Prefix,
+                candidate.len() - query_lower.len(),
+                term.clone(),
+            ));
+            seen.insert(term.id);
+        }
+    }
+
+    let budget = typo_budget(query_chars.len());
+    let tree = BkTree::build(terms);
+    for (distance, term) in tree.fuzzy_matches(&query_chars, budget) {
+        if seen.insert(term.id) {
+            ranked.push((MatchClass::Fuzzy, distance, term));
+        }
+    }
+
+    ranked.sort_by(|(class_a, dist_a, term_a), (class_b, dist_b, term_b)| {
+        class_a
+            .cmp(class_b)
+            .then(dist_a.cmp(dist_b))
+            .then_with(|| term_a.term.to_lowercase().cmp(&term_b.term.to_lowercase()))
+    });
+
+    ranked.into_iter().take(limit).map(|(_, _, term)| term).collect()
+}
 
-    Ok(terms)
+#[tauri::command]
+async fn search_terms(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Term>, String> {
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+    Ok(fuzzy_search_terms(&terms, &query, limit.unwrap_or(20)))
 }
 
 #[tauri::command]
@@ -1083,35 +2027,12 @@ async fn find_term_by_name(
     term: String,
     state: State<'_, AppState>,
 ) -> Result<Option<Term>, String> {
-    let record = sqlx::query(
-        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at FROM terms WHERE lower(term) = lower(?) LIMIT 1",
-    )
-    .bind(&term)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|err| err.to_string())?;
-
-    let result = record.map(|row| Term {
-        id: row.get("id"),
-        term: row.get("term"),
-        definition: row.get("definition"),
-        definition_cn: row.get("definition_cn"),
-        review_stage: row.get("review_stage"),
-        last_reviewed_at: row.get("last_reviewed_at"),
-    });
-
-    Ok(result)
+    lexai_core::find_term_by_name(&state.pool().await, &term).await
 }
 
 #[tauri::command]
 async fn delete_term(id: i64, state: State<'_, AppState>) -> Result<(), String> {
-    sqlx::query("DELETE FROM terms WHERE id = ?")
-        .bind(id)
-        .execute(&state.pool)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    Ok(())
+    lexai_core::delete_term(&state.pool().await, id).await
 }
 
 #[tauri::command]
@@ -1122,16 +2043,7 @@ async fn update_term(
     definition_cn: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    sqlx::query("UPDATE terms SET term = ?, definition = ?, definition_cn = COALESCE(?, definition_cn) WHERE id = ?")
-        .bind(term)
-        .bind(definition)
-        .bind(definition_cn)
-        .bind(id)
-        .execute(&state.pool)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    Ok(())
+    lexai_core::update_term(&state.pool().await, id, term, definition, definition_cn).await
 }
 
 #[tauri::command]
@@ -1139,12 +2051,12 @@ async fn export_terms_csv(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    let terms = load_terms_sorted(&state.pool).await?;
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
     if terms.is_empty() {
         return Err("No terms available to export.".to_string());
     }
 
-    let csv = spawn_blocking(move || build_csv(&terms))
+    let csv = spawn_blocking(move || lexai_core::build_csv(&terms))
         .await
         .map_err(|err| err.to_string())??;
 
@@ -1164,20 +2076,6 @@ async fn export_terms_csv(
     Ok(())
 }
 
-fn build_csv(terms: &[Term]) -> Result<String, String> {
-    let mut csv = String::from("Term,Definition,Definition (zh-CN)\n");
-    for entry in terms {
-        let line = format!(
-            "{},{},{}\n",
-            escape_csv_cell(&entry.term),
-            escape_csv_cell(&entry.definition),
-            escape_csv_cell(entry.definition_cn.as_deref().unwrap_or(""))
-        );
-        csv.push_str(&line);
-    }
-    Ok(csv)
-}
-
 async fn prompt_save_path(
     app_handle: &tauri::AppHandle,
     default_file_name: &str,
@@ -1213,40 +2111,12 @@ async fn prompt_save_path(
     file_path.into_path().map_err(|err| err.to_string())
 }
 
-async fn load_terms_sorted(pool: &SqlitePool) -> Result<Vec<Term>, String> {
-    let records = sqlx::query(
-        "SELECT id, term, COALESCE(definition, '') AS definition, COALESCE(definition_cn, '') AS definition_cn, review_stage, last_reviewed_at FROM terms ORDER BY lower(term) ASC",
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|err| err.to_string())?;
-
-    Ok(records
-        .into_iter()
-        .map(|row| Term {
-            id: row.get("id"),
-            term: row.get("term"),
-            definition: row.get("definition"),
-            definition_cn: {
-                let value: String = row.get("definition_cn");
-                if value.is_empty() {
-                    None
-                } else {
-                    Some(value)
-                }
-            },
-            review_stage: row.get("review_stage"),
-            last_reviewed_at: row.get("last_reviewed_at"),
-        })
-        .collect())
-}
-
 #[tauri::command]
 async fn export_terms_anki(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    let terms = load_terms_sorted(&state.pool).await?;
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
     if terms.is_empty() {
         return Err("No terms available to export.".to_string());
     }
@@ -1261,36 +2131,19 @@ async fn export_terms_anki(
     .await?;
 
     let deck_terms = terms.clone();
-    spawn_blocking(move || build_anki_package(&path, &deck_terms))
+    spawn_blocking(move || lexai_core::build_anki_package(&path, &deck_terms))
         .await
         .map_err(|err| err.to_string())??;
 
     Ok(())
 }
 
-fn build_anki_package(path: &Path, terms: &[Term]) -> Result<(), String> {
-    let mut deck = Deck::new(805_202_110, "LexAI Termbase", "Exported from LexAI");
-    let model = basic_model();
-
-    for term in terms {
-        let definition_cn = term.definition_cn.as_deref();
-        let combined = build_anki_back_field(&term.definition, definition_cn);
-
-        let note = Note::new(model.clone(), vec![term.term.as_str(), &combined])
-            .map_err(|err: AnkiError| err.to_string())?;
-        deck.add_note(note);
-    }
-
-    deck.write_to_file(path.to_str().ok_or("Invalid path for Anki export")?)
-        .map_err(|err| err.to_string())
-}
-
 #[tauri::command]
 async fn export_terms_pdf(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    let terms = load_terms_sorted(&state.pool).await?;
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
     if terms.is_empty() {
         return Err("No terms available to export.".to_string());
     }
@@ -1305,106 +2158,30 @@ async fn export_terms_pdf(
     .await?;
 
     let printable_terms = terms.clone();
-    spawn_blocking(move || build_pdf(&path, &printable_terms))
+    spawn_blocking(move || lexai_core::build_pdf(&path, &printable_terms))
         .await
         .map_err(|err| err.to_string())??;
 
     Ok(())
 }
 
-fn build_pdf(path: &Path, terms: &[Term]) -> Result<(), String> {
-    let font_family = load_pdf_font_family()?;
-
-    let mut doc = Document::new(font_family);
-    doc.set_title("LexAI Terminology Export");
-    doc.set_minimal_conformance();
-
-    for term in terms {
-        let heading = StyledElement::new(Paragraph::new(term.term.clone()), Effect::Bold);
-        doc.push(heading);
-
-        doc.push(Paragraph::new(sanitize_pdf_text(&term.definition)));
-
-        if let Some(def_cn) = term.definition_cn.as_deref() {
-            if !def_cn.is_empty() {
-                doc.push(Paragraph::new(sanitize_pdf_text(def_cn)));
-            }
-        }
-
-        doc.push(Break::new(1.2));
-    }
-
-    doc.render_to_file(path)
-        .map_err(|err| format!("Failed to write PDF: {err}"))
-}
-
 #[tauri::command]
 async fn get_review_terms(
     state: State<'_, AppState>,
     limit: Option<i64>,
 ) -> Result<Vec<Term>, String> {
-    let limit = limit.unwrap_or(20).clamp(1, 100);
-
-    let records = sqlx::query(
-        "SELECT id, term, COALESCE(definition, '') AS definition, definition_cn, review_stage, last_reviewed_at FROM terms ORDER BY review_stage ASC, COALESCE(last_reviewed_at, '') ASC, created_at ASC LIMIT ?",
-    )
-    .bind(limit)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(|err| err.to_string())?;
-
-    let terms = records
-        .into_iter()
-        .map(|row| Term {
-            id: row.get("id"),
-            term: row.get("term"),
-            definition: row.get("definition"),
-            definition_cn: row.get("definition_cn"),
-            review_stage: row.get("review_stage"),
-            last_reviewed_at: row.get("last_reviewed_at"),
-        })
-        .collect();
-
-    Ok(terms)
+    lexai_core::get_review_terms(&state.pool().await, limit).await
 }
 
+/// `quality` is the SM-2 recall grade, 0 (total blank) to 5 (perfect
+/// recall); anything below 3 counts as a lapse and resets the interval.
 #[tauri::command]
 async fn submit_review_result(
     id: i64,
-    known: bool,
+    quality: i64,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    apply_review_result(&state.pool, id, known).await
-}
-
-async fn apply_review_result(pool: &SqlitePool, id: i64, known: bool) -> Result<(), String> {
-    let record = sqlx::query("SELECT review_stage FROM terms WHERE id = ?")
-        .bind(id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    let Some(row) = record else {
-        return Err("Term not found".to_string());
-    };
-
-    let current_stage: i64 = row.get("review_stage");
-    let next_stage = if known {
-        current_stage.saturating_add(1).min(5)
-    } else {
-        current_stage.saturating_sub(1)
-    };
-    let timestamp = Utc::now().to_rfc3339();
-
-    sqlx::query("UPDATE terms SET review_stage = ?, last_reviewed_at = ? WHERE id = ?")
-        .bind(next_stage)
-        .bind(timestamp)
-        .bind(id)
-        .execute(pool)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    Ok(())
+    lexai_core::apply_review_result(&state.pool().await, id, quality).await
 }
 
 fn normalize_lower(s: &str) -> String {
@@ -1565,42 +2342,542 @@ async fn has_api_key(
     Ok(false)
 }
 
-async fn init_database(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
-    let connect_options = SqliteConnectOptions::new()
-        .filename(db_path)
-        .create_if_missing(true)
-        .disable_statement_logging();
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect_with(connect_options)
-        .await?;
+/// Unlocks the secrets vault with the user's master passphrase, deriving
+/// the Stronghold key via Argon2id. On first unlock this also creates the
+/// snapshot and its passphrase verifier; on later unlocks a wrong
+/// passphrase is rejected via that verifier rather than silently opening a
+/// garbage vault. Runs the legacy apiKey migration once the vault is open.
+#[tauri::command]
+async fn unlock_vault(
+    passphrase: String,
+    app: tauri::AppHandle,
+    manager: State<'_, SecretsManager>,
+) -> Result<(), String> {
+    manager.unlock(&passphrase).await?;
+    // The legacy flat apiKey array only ever existed in the default
+    // profile's config, so importing it only makes sense once, into the
+    // default profile's vault namespace.
+    if manager.active_client_path().await == STRONGHOLD_CLIENT_PATH {
+        migrate_legacy_api_keys(&app, &manager).await?;
+    }
+    Ok(())
+}
 
-    sqlx::migrate!("./migrations").run(&pool).await?;
-    Ok(pool)
+#[tauri::command]
+async fn is_vault_unlocked(manager: State<'_, SecretsManager>) -> Result<bool, String> {
+    Ok(manager.is_unlocked().await)
 }
 
-fn migrate_legacy_api_keys(
-    app: &tauri::App,
-    secrets_manager: &SecretsManager,
+/// Re-encrypts every stored secret under a new master passphrase. Fails
+/// without side effects if `old` doesn't match the vault's current
+/// passphrase.
+#[tauri::command]
+async fn change_master_passphrase(
+    old: String,
+    new: String,
+    app: tauri::AppHandle,
+    manager: State<'_, SecretsManager>,
 ) -> Result<(), String> {
+    let client_paths: Vec<Vec<u8>> = load_profiles(&app)?
+        .iter()
+        .map(|profile| profile_client_path(&profile.id))
+        .collect();
+    manager.rekey(&old, &new, &client_paths).await
+}
+
+// --- Multi-profile support -------------------------------------------------
+//
+// Every profile shares the one Stronghold snapshot managed by
+// `SecretsManager`, distinguished only by its `client_path` namespace within
+// it (see `SecretsManager::switch_profile`); each profile gets its own
+// SQLite file. Profile metadata (id/name) lives in `lexai-config.store`
+// under "profiles", alongside the S3 remotes and legacy provider array. The
+// "default" profile is never written to that list — it always exists
+// implicitly under the pre-existing `lexai.db` filename and
+// `STRONGHOLD_CLIENT_PATH`, so upgrading from a single-profile install needs
+// no migration.
+
+const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Emitted on launch when more than one profile exists, so the frontend can
+/// prompt for a choice instead of silently trusting whichever profile
+/// `.setup()` defaulted to.
+const EVT_PROFILE_SELECT_REQUIRED: &str = "profile://select-required";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Profile {
+    id: String,
+    name: String,
+}
+
+fn profile_db_path(data_dir: &Path, profile_id: &str) -> PathBuf {
+    if profile_id == DEFAULT_PROFILE_ID {
+        data_dir.join("lexai.db")
+    } else {
+        data_dir.join(format!("lexai-{profile_id}.db"))
+    }
+}
+
+fn profile_client_path(profile_id: &str) -> Vec<u8> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        STRONGHOLD_CLIENT_PATH.to_vec()
+    } else {
+        format!("lexai_api_credentials::{profile_id}").into_bytes()
+    }
+}
+
+/// Always includes the implicit `"default"` profile first, even if it has
+/// never been persisted to the config store.
+fn load_profiles(app: &tauri::AppHandle) -> Result<Vec<Profile>, String> {
     let config_store = app
         .store("lexai-config.store")
         .map_err(|err| err.to_string())?;
 
-    let Some(JsonValue::Array(mut providers)) = config_store.get("providers") else {
-        return Ok(());
+    let mut profiles: Vec<Profile> = match config_store.get("profiles") {
+        Some(JsonValue::Array(entries)) => entries
+            .into_iter()
+            .filter_map(|entry| serde_json::from_value(entry).ok())
+            .collect(),
+        _ => Vec::new(),
     };
 
-    let mut changed = false;
+    if !profiles.iter().any(|profile| profile.id == DEFAULT_PROFILE_ID) {
+        profiles.insert(
+            0,
+            Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+            },
+        );
+    }
 
-    for provider in providers.iter_mut() {
-        let Some(object) = provider.as_object_mut() else {
-            continue;
-        };
+    Ok(profiles)
+}
 
-        let Some(provider_id) = object
-            .get("id")
+/// Persists every profile except the implicit `"default"` one.
+fn save_profiles(app: &tauri::AppHandle, profiles: &[Profile]) -> Result<(), String> {
+    let config_store = app
+        .store("lexai-config.store")
+        .map_err(|err| err.to_string())?;
+
+    let persisted: Vec<&Profile> = profiles
+        .iter()
+        .filter(|profile| profile.id != DEFAULT_PROFILE_ID)
+        .collect();
+    config_store.set("profiles", json!(persisted));
+    config_store.save().map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn list_profiles(app: tauri::AppHandle) -> Result<Vec<Profile>, String> {
+    load_profiles(&app)
+}
+
+/// Creates a new, empty profile and switches the running app to it.
+#[tauri::command]
+async fn create_profile(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    secrets_manager: State<'_, SecretsManager>,
+) -> Result<Profile, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name cannot be empty.".to_string());
+    }
+
+    let mut profiles = load_profiles(&app)?;
+    let profile = Profile {
+        id: Uuid::new_v4().to_string(),
+        name: trimmed.to_string(),
+    };
+    profiles.push(profile.clone());
+    save_profiles(&app, &profiles)?;
+
+    activate_profile(&app, &profile, &state, &secrets_manager).await?;
+    Ok(profile)
+}
+
+/// Switches the running app to an already-existing profile's database and
+/// secrets namespace.
+#[tauri::command]
+async fn switch_profile(
+    profile_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    secrets_manager: State<'_, SecretsManager>,
+) -> Result<(), String> {
+    let profiles = load_profiles(&app)?;
+    let profile = profiles
+        .into_iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("No profile found with id '{profile_id}'."))?;
+
+    activate_profile(&app, &profile, &state, &secrets_manager).await
+}
+
+/// Deletes a profile's metadata and database file. Refuses to delete the
+/// `"default"` profile (it isn't a real entry to begin with) or the profile
+/// that's currently active, since that would leave the app pointed at a
+/// deleted database.
+#[tauri::command]
+async fn delete_profile(
+    profile_id: String,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+) -> Result<(), String> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return Err("The default profile cannot be deleted.".to_string());
+    }
+    if secrets_manager.active_client_path().await == profile_client_path(&profile_id) {
+        return Err("Cannot delete the currently active profile.".to_string());
+    }
+
+    let mut profiles = load_profiles(&app)?;
+    let before = profiles.len();
+    profiles.retain(|profile| profile.id != profile_id);
+    if profiles.len() == before {
+        return Err(format!("No profile found with id '{profile_id}'."));
+    }
+    save_profiles(&app, &profiles)?;
+
+    let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let db_path = profile_db_path(&data_dir, &profile_id);
+    if db_path.exists() {
+        fs::remove_file(&db_path).map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Shared by `create_profile`/`switch_profile`: opens (or creates) the
+/// profile's database and re-points `AppState` and `SecretsManager` at it.
+async fn activate_profile(
+    app: &tauri::AppHandle,
+    profile: &Profile,
+    state: &AppState,
+    secrets_manager: &SecretsManager,
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    let db_path = profile_db_path(&data_dir, &profile.id);
+
+    let pool = lexai_core::init_database(&db_path)
+        .await
+        .map_err(|err| err.to_string())?;
+    state.set_pool(pool).await;
+    secrets_manager
+        .switch_profile(profile_client_path(&profile.id))
+        .await;
+
+    Ok(())
+}
+
+/// Embedding vendor recognized by `embed_all_terms`/`semantic_search_terms`,
+/// resolved from the same vendor strings `provider_aliases` already knows
+/// about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EmbeddingProvider {
+    OpenAi,
+    Gemini,
+    Ollama,
+}
+
+impl EmbeddingProvider {
+    fn from_vendor(vendor: &str) -> Option<Self> {
+        match normalize_lower(vendor).as_str() {
+            "openai" => Some(Self::OpenAi),
+            "google" | "gemini" => Some(Self::Gemini),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+
+    fn model(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "text-embedding-3-small",
+            Self::Gemini => "embedding-001",
+            Self::Ollama => "nomic-embed-text",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingData {
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingResponse {
+    data: Vec<OpenAiEmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbeddingValues {
+    values: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbeddingResponse {
+    embedding: GeminiEmbeddingValues,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+/// Calls the provider's embedding endpoint for a single piece of text and
+/// returns the raw (not yet normalized) vector. `api_key` is required for
+/// OpenAI and Gemini and ignored for Ollama, which runs unauthenticated on
+/// localhost.
+async fn embed_text(
+    provider: EmbeddingProvider,
+    api_key: Option<&str>,
+    text: &str,
+) -> Result<Vec<f32>, String> {
+    let client = HttpClient::new();
+    match provider {
+        EmbeddingProvider::OpenAi => {
+            let key = api_key.ok_or("No OpenAI API key configured.")?;
+            let response = client
+                .post("https://api.openai.com/v1/embeddings")
+                .bearer_auth(key)
+                .json(&json!({ "model": provider.model(), "input": text }))
+                .send()
+                .await
+                .map_err(|err| err.to_string())?
+                .error_for_status()
+                .map_err(|err| err.to_string())?
+                .json::<OpenAiEmbeddingResponse>()
+                .await
+                .map_err(|err| err.to_string())?;
+            response
+                .data
+                .into_iter()
+                .next()
+                .map(|d| d.embedding)
+                .ok_or_else(|| "OpenAI returned no embedding data.".to_string())
+        }
+        EmbeddingProvider::Gemini => {
+            let key = api_key.ok_or("No Gemini API key configured.")?;
+            let url = format!(
+                "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent?key={}",
+                provider.model(),
+                key
+            );
+            let response = client
+                .post(url)
+                .json(&json!({
+                    "model": format!("models/{}", provider.model()),
+                    "content": { "parts": [{ "text": text }] },
+                }))
+                .send()
+                .await
+                .map_err(|err| err.to_string())?
+                .error_for_status()
+                .map_err(|err| err.to_string())?
+                .json::<GeminiEmbeddingResponse>()
+                .await
+                .map_err(|err| err.to_string())?;
+            Ok(response.embedding.values)
+        }
+        EmbeddingProvider::Ollama => {
+            let response = client
+                .post("http://localhost:11434/api/embeddings")
+                .json(&json!({ "model": provider.model(), "prompt": text }))
+                .send()
+                .await
+                .map_err(|err| err.to_string())?
+                .error_for_status()
+                .map_err(|err| err.to_string())?
+                .json::<OllamaEmbeddingResponse>()
+                .await
+                .map_err(|err| err.to_string())?;
+            Ok(response.embedding)
+        }
+    }
+}
+
+fn l2_normalize(vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector;
+    }
+    vector.into_iter().map(|v| v / norm).collect()
+}
+
+fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().expect("chunks_exact(4) yields 4 bytes")))
+        .collect()
+}
+
+fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Plain-text representation of a term used as embedding input: combining
+/// the headword with both definitions gives the provider more context than
+/// embedding the term alone.
+fn term_embedding_text(term: &Term) -> String {
+    match &term.definition_cn {
+        Some(cn) if !cn.is_empty() => format!("{}: {} ({})", term.term, term.definition, cn),
+        _ => format!("{}: {}", term.term, term.definition),
+    }
+}
+
+/// Resolves the configured provider's vendor and API key the same way
+/// `get_api_key`/`has_api_key` do: try the bare provider string first, then
+/// fall back through `provider_aliases`.
+async fn resolve_embedding_provider(
+    app: &tauri::AppHandle,
+    provider: &str,
+    secrets_manager: &SecretsManager,
+) -> Result<(EmbeddingProvider, Option<String>), String> {
+    let embedding_provider = EmbeddingProvider::from_vendor(provider)
+        .ok_or_else(|| format!("Unknown embedding provider '{}'.", provider))?;
+
+    if embedding_provider == EmbeddingProvider::Ollama {
+        return Ok((embedding_provider, None));
+    }
+
+    for alias in provider_aliases(app, provider) {
+        if let Some(key) = secrets_manager.get_api_key(&alias).await? {
+            return Ok((embedding_provider, Some(key)));
+        }
+    }
+
+    Err(format!("No API key configured for '{}'.", provider))
+}
+
+/// (Re-)embeds every term whose stored embedding is missing or was produced
+/// by a different model than the one currently configured for `provider`,
+/// so switching providers naturally triggers a re-embed on the next run.
+/// Returns the number of terms embedded.
+#[tauri::command]
+async fn embed_all_terms(
+    provider: String,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let (embedding_provider, api_key) =
+        resolve_embedding_provider(&app, &provider, &secrets_manager).await?;
+    let model = embedding_provider.model();
+
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+    let existing_models: HashMap<i64, String> =
+        sqlx::query("SELECT term_id, model FROM embeddings")
+            .fetch_all(&state.pool().await)
+            .await
+            .map_err(|err| err.to_string())?
+            .into_iter()
+            .map(|row| (row.get("term_id"), row.get("model")))
+            .collect();
+
+    let mut embedded = 0usize;
+    for term in &terms {
+        if existing_models.get(&term.id).map(String::as_str) == Some(model) {
+            continue;
+        }
+
+        let text = term_embedding_text(term);
+        let vector = l2_normalize(embed_text(embedding_provider, api_key.as_deref(), &text).await?);
+        let blob = vector_to_blob(&vector);
+
+        sqlx::query(
+            "INSERT INTO embeddings (term_id, model, vector, dims) VALUES (?, ?, ?, ?)
+             ON CONFLICT(term_id) DO UPDATE SET model = excluded.model, vector = excluded.vector, dims = excluded.dims",
+        )
+        .bind(term.id)
+        .bind(model)
+        .bind(blob)
+        .bind(vector.len() as i64)
+        .execute(&state.pool().await)
+        .await
+        .map_err(|err| err.to_string())?;
+
+        embedded += 1;
+    }
+
+    Ok(embedded)
+}
+
+/// Ranks terms by cosine similarity (dot product over L2-normalized
+/// vectors) between the query and each term's stored embedding, falling
+/// back to the lexical `fuzzy_search_terms` path when no embeddings exist
+/// yet for the configured provider's model.
+#[tauri::command]
+async fn semantic_search_terms(
+    query: String,
+    provider: String,
+    top_k: Option<usize>,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Term>, String> {
+    let limit = top_k.unwrap_or(20);
+    let (embedding_provider, api_key) =
+        resolve_embedding_provider(&app, &provider, &secrets_manager).await?;
+    let model = embedding_provider.model();
+
+    let rows = sqlx::query("SELECT term_id, vector FROM embeddings WHERE model = ?")
+        .bind(model)
+        .fetch_all(&state.pool().await)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if rows.is_empty() {
+        let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+        return Ok(fuzzy_search_terms(&terms, &query, limit));
+    }
+
+    let embedded_query = l2_normalize(embed_text(embedding_provider, api_key.as_deref(), &query).await?);
+
+    let mut scored: Vec<(f32, i64)> = rows
+        .into_iter()
+        .map(|row| {
+            let term_id: i64 = row.get("term_id");
+            let blob: Vec<u8> = row.get("vector");
+            (dot_product(&embedded_query, &blob_to_vector(&blob)), term_id)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(limit);
+
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+    let by_id: HashMap<i64, Term> = terms.into_iter().map(|t| (t.id, t)).collect();
+    Ok(scored
+        .into_iter()
+        .filter_map(|(_, term_id)| by_id.get(&term_id).cloned())
+        .collect())
+}
+
+async fn migrate_legacy_api_keys(
+    app: &tauri::AppHandle,
+    secrets_manager: &SecretsManager,
+) -> Result<(), String> {
+    let config_store = app
+        .store("lexai-config.store")
+        .map_err(|err| err.to_string())?;
+
+    let Some(JsonValue::Array(mut providers)) = config_store.get("providers") else {
+        return Ok(());
+    };
+
+    let mut changed = false;
+
+    for provider in providers.iter_mut() {
+        let Some(object) = provider.as_object_mut() else {
+            continue;
+        };
+
+        let Some(provider_id) = object
+            .get("id")
             .and_then(|value| value.as_str())
             .map(str::to_string)
         else {
@@ -1618,10 +2895,9 @@ fn migrate_legacy_api_keys(
                 .map(str::trim)
                 .filter(|value| !value.is_empty())
             {
-                tauri::async_runtime::block_on(
-                    secrets_manager.save_api_key(&provider_id, api_key_str),
-                )
-                .map_err(|err| err.to_string())?;
+                secrets_manager
+                    .save_api_key(&provider_id, api_key_str)
+                    .await?;
             }
         }
     }
@@ -1634,6 +2910,628 @@ fn migrate_legacy_api_keys(
     Ok(())
 }
 
+// --- Backup/restore subsystem -------------------------------------------
+//
+// Each backup is a directory containing a plaintext `header.json` (format
+// version + Argon2id salt), a content-addressed, encrypted `chunks/`
+// store keyed by BLAKE3 digest, and an encrypted `manifest.bin` mapping
+// each backed-up file to its ordered list of chunk hashes. Repeated
+// backups against the same directory only write chunks that aren't
+// already present, so unchanged files cost near nothing to re-back-up.
+
+const CDC_WINDOW: usize = 64;
+const CDC_MIN_CHUNK: usize = 2 * 1024;
+const CDC_MAX_CHUNK: usize = 64 * 1024;
+/// Mask tuned so a boundary is expected roughly every 2^14 = 16 KiB.
+const CDC_MASK: u64 = (1 << 14) - 1;
+const BACKUP_HEADER_FILE: &str = "header.json";
+const BACKUP_MANIFEST_FILE: &str = "manifest.bin";
+const BACKUP_CHUNKS_DIR: &str = "chunks";
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Byte-indexed Gear table used by the content-defined chunker, derived
+/// deterministically from BLAKE3 so there's no large literal table to
+/// maintain in source.
+fn gear_table() -> &'static [u64; 256] {
+    static TABLE: OnceLock<[u64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u64; 256];
+        for (index, slot) in table.iter_mut().enumerate() {
+            let digest = hash(&[index as u8]);
+            *slot = u64::from_le_bytes(digest.as_bytes()[0..8].try_into().unwrap());
+        }
+        table
+    })
+}
+
+/// Splits `data` into content-defined chunks using a Gear rolling hash over
+/// a sliding `CDC_WINDOW`-byte window: a boundary falls wherever
+/// `hash & CDC_MASK == 0`, subject to `CDC_MIN_CHUNK`/`CDC_MAX_CHUNK` bounds
+/// so pathological inputs (e.g. all-zero runs) still produce bounded
+/// chunks. Chunking this way means two backups of a mostly-unchanged file
+/// share almost all of their chunks even if bytes were inserted or removed
+/// in the middle.
+fn cdc_chunks(data: &[u8]) -> Vec<&[u8]> {
+    let gear = gear_table();
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    let mut hash_value: u64 = 0;
+    let mut window: VecDeque<u8> = VecDeque::with_capacity(CDC_WINDOW);
+
+    for (offset, &byte) in data.iter().enumerate() {
+        hash_value = hash_value.rotate_left(1) ^ gear[byte as usize];
+        window.push_back(byte);
+        if window.len() > CDC_WINDOW {
+            let outgoing = window.pop_front().unwrap();
+            hash_value ^= gear[outgoing as usize];
+        }
+
+        let chunk_len = offset - start + 1;
+        if chunk_len >= CDC_MAX_CHUNK || (chunk_len >= CDC_MIN_CHUNK && hash_value & CDC_MASK == 0)
+        {
+            chunks.push(&data[start..=offset]);
+            start = offset + 1;
+            hash_value = 0;
+            window.clear();
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+
+    chunks
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupHeader {
+    version: u32,
+    salt_hex: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    /// Maps an app-data file name (e.g. "lexai.db") to the ordered list of
+    /// hex-encoded chunk hashes that reassemble it.
+    files: HashMap<String, Vec<String>>,
+}
+
+fn derive_backup_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key_bytes = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key_bytes)
+        .map_err(|err| err.to_string())?;
+    Ok(*Key::from_slice(&key_bytes))
+}
+
+fn encrypt_bytes(cipher: &XChaCha20Poly1305, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|err| err.to_string())?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt_bytes(cipher: &XChaCha20Poly1305, blob: &[u8]) -> Result<Vec<u8>, String> {
+    if blob.len() < 24 {
+        return Err("Backup chunk is truncated.".to_string());
+    }
+    let (nonce_bytes, ciphertext) = blob.split_at(24);
+    let nonce = XNonce::from_slice(nonce_bytes);
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|err| err.to_string())
+}
+
+/// Splits `data` into content-defined chunks, encrypts each new one (chunks
+/// already present in `chunks_dir` are left untouched) and returns the
+/// ordered list of hex chunk hashes that reassemble `data`.
+fn write_file_chunks(
+    cipher: &XChaCha20Poly1305,
+    chunks_dir: &Path,
+    data: &[u8],
+) -> Result<Vec<String>, String> {
+    let mut hashes = Vec::new();
+    for chunk in cdc_chunks(data) {
+        let digest = hash(chunk).to_hex().to_string();
+        let chunk_path = chunks_dir.join(&digest);
+        if !chunk_path.exists() {
+            let encrypted = encrypt_bytes(cipher, chunk)?;
+            fs::write(&chunk_path, encrypted).map_err(|err| err.to_string())?;
+        }
+        hashes.push(digest);
+    }
+    Ok(hashes)
+}
+
+fn read_file_chunks(
+    cipher: &XChaCha20Poly1305,
+    chunks_dir: &Path,
+    hashes: &[String],
+) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    for digest in hashes {
+        let encrypted = fs::read(chunks_dir.join(digest)).map_err(|err| err.to_string())?;
+        data.extend_from_slice(&decrypt_bytes(cipher, &encrypted)?);
+    }
+    Ok(data)
+}
+
+/// App-data files that together make up a full backup: every profile's
+/// terminology database (the default profile's is the pre-existing
+/// `lexai.db`), the provider config store, and the encrypted secrets vault.
+fn backup_source_files(
+    app: &tauri::AppHandle,
+    data_dir: &Path,
+) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files: Vec<(String, PathBuf)> = load_profiles(app)?
+        .iter()
+        .map(|profile| {
+            let db_path = profile_db_path(data_dir, &profile.id);
+            let name = db_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("lexai.db")
+                .to_string();
+            (name, db_path)
+        })
+        .collect();
+    files.push((
+        "lexai-config.store".to_string(),
+        data_dir.join("lexai-config.store"),
+    ));
+    files.push((
+        STRONGHOLD_SNAPSHOT.to_string(),
+        data_dir.join(STRONGHOLD_SNAPSHOT),
+    ));
+    Ok(files)
+}
+
+fn run_create_backup(
+    app: &tauri::AppHandle,
+    backup_dir: PathBuf,
+    passphrase: String,
+    data_dir: PathBuf,
+) -> Result<(), String> {
+    let chunks_dir = backup_dir.join(BACKUP_CHUNKS_DIR);
+    fs::create_dir_all(&chunks_dir).map_err(|err| err.to_string())?;
+
+    let header_path = backup_dir.join(BACKUP_HEADER_FILE);
+    let salt = if header_path.exists() {
+        let header: BackupHeader = serde_json::from_slice(
+            &fs::read(&header_path).map_err(|err| err.to_string())?,
+        )
+        .map_err(|err| err.to_string())?;
+        hex::decode(header.salt_hex).map_err(|err| err.to_string())?
+    } else {
+        let mut salt = vec![0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let header = BackupHeader {
+            version: BACKUP_FORMAT_VERSION,
+            salt_hex: hex::encode(&salt),
+        };
+        fs::write(&header_path, serde_json::to_vec(&header).map_err(|err| err.to_string())?)
+            .map_err(|err| err.to_string())?;
+        salt
+    };
+
+    let key = derive_backup_key(&passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(&key);
+
+    // Chunks are deduplicated by content digest alone, independent of the
+    // key they're encrypted under. If an existing backup is re-run with a
+    // different passphrase, any unchanged chunk would be skip-written and
+    // left on disk under the old key while the manifest (always rewritten)
+    // expects the new one, silently breaking restore for that file. Refuse
+    // instead of producing a backup that looks fine but can't be restored.
+    let manifest_path = backup_dir.join(BACKUP_MANIFEST_FILE);
+    if manifest_path.exists() {
+        let encrypted_manifest = fs::read(&manifest_path).map_err(|err| err.to_string())?;
+        decrypt_bytes(&cipher, &encrypted_manifest).map_err(|_| {
+            "Wrong passphrase for the existing backup at this location.".to_string()
+        })?;
+    }
+
+    let mut files = HashMap::new();
+    for (name, source_path) in backup_source_files(app, &data_dir)? {
+        if !source_path.exists() {
+            continue;
+        }
+        let data = fs::read(&source_path).map_err(|err| err.to_string())?;
+        let hashes = write_file_chunks(&cipher, &chunks_dir, &data)?;
+        files.insert(name, hashes);
+    }
+
+    let manifest = BackupManifest { files };
+    let manifest_bytes = serde_json::to_vec(&manifest).map_err(|err| err.to_string())?;
+    let encrypted_manifest = encrypt_bytes(&cipher, &manifest_bytes)?;
+    fs::write(backup_dir.join(BACKUP_MANIFEST_FILE), encrypted_manifest)
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+fn run_restore_backup(backup_dir: PathBuf, passphrase: String, data_dir: PathBuf) -> Result<(), String> {
+    let header: BackupHeader = serde_json::from_slice(
+        &fs::read(backup_dir.join(BACKUP_HEADER_FILE)).map_err(|err| err.to_string())?,
+    )
+    .map_err(|err| err.to_string())?;
+    let salt = hex::decode(header.salt_hex).map_err(|err| err.to_string())?;
+
+    let key = derive_backup_key(&passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(&key);
+
+    let encrypted_manifest =
+        fs::read(backup_dir.join(BACKUP_MANIFEST_FILE)).map_err(|err| err.to_string())?;
+    let manifest_bytes = decrypt_bytes(&cipher, &encrypted_manifest)
+        .map_err(|_| "Wrong passphrase or corrupted backup.".to_string())?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&manifest_bytes).map_err(|err| err.to_string())?;
+
+    let chunks_dir = backup_dir.join(BACKUP_CHUNKS_DIR);
+    for (name, hashes) in &manifest.files {
+        let data = read_file_chunks(&cipher, &chunks_dir, hashes)?;
+        fs::write(data_dir.join(name), data).map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Creates or updates an incremental, encrypted backup of the application's
+/// data (terminology DB, config store, secrets vault) at `path`. Reusing
+/// the same `path` across calls only writes chunks that changed since the
+/// last backup.
+#[tauri::command]
+async fn create_backup(path: String, passphrase: String, app: tauri::AppHandle) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    spawn_blocking(move || run_create_backup(&app, PathBuf::from(path), passphrase, data_dir))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Restores the application's data from a backup directory created by
+/// `create_backup`, overwriting the current terminology DB, config store,
+/// and secrets vault.
+#[tauri::command]
+async fn restore_backup(path: String, passphrase: String, app: tauri::AppHandle) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+    spawn_blocking(move || run_restore_backup(PathBuf::from(path), passphrase, data_dir))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+// --- S3-compatible remote sync -------------------------------------------
+//
+// Config (endpoint/region/bucket/prefix) lives in `lexai-config.store`
+// under "s3_remotes", alongside the existing `providers` array. Credentials
+// go through `SecretsManager` like any other provider, keyed by a pair of
+// synthetic provider names derived from `remote_id`. Requests are signed
+// with AWS Signature V4 directly so pushing/pulling works against any
+// S3-compatible gateway (AWS S3, MinIO, Garage, ...), not just AWS.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct S3RemoteConfig {
+    id: String,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+}
+
+fn s3_access_key_provider(remote_id: &str) -> String {
+    format!("s3-access-key::{remote_id}")
+}
+
+fn s3_secret_key_provider(remote_id: &str) -> String {
+    format!("s3-secret-key::{remote_id}")
+}
+
+/// Adds or updates an S3-compatible remote: its connection config goes into
+/// the config store, its credentials into `SecretsManager`.
+#[tauri::command]
+async fn save_s3_remote(
+    remote_id: String,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+) -> Result<(), String> {
+    let config_store = app
+        .store("lexai-config.store")
+        .map_err(|err| err.to_string())?;
+
+    let mut remotes = match config_store.get("s3_remotes") {
+        Some(JsonValue::Array(entries)) => entries,
+        _ => Vec::new(),
+    };
+    remotes.retain(|entry| entry.get("id").and_then(|v| v.as_str()) != Some(remote_id.as_str()));
+    remotes.push(json!({
+        "id": remote_id,
+        "endpoint": endpoint,
+        "region": region,
+        "bucket": bucket,
+        "prefix": prefix,
+    }));
+    config_store.set("s3_remotes", JsonValue::Array(remotes));
+    config_store.save().map_err(|err| err.to_string())?;
+
+    secrets_manager
+        .save_api_key(&s3_access_key_provider(&remote_id), &access_key)
+        .await?;
+    secrets_manager
+        .save_api_key(&s3_secret_key_provider(&remote_id), &secret_key)
+        .await?;
+
+    Ok(())
+}
+
+fn load_s3_remote_config(app: &tauri::AppHandle, remote_id: &str) -> Result<S3RemoteConfig, String> {
+    let config_store = app
+        .store("lexai-config.store")
+        .map_err(|err| err.to_string())?;
+
+    let Some(JsonValue::Array(remotes)) = config_store.get("s3_remotes") else {
+        return Err(format!("No S3 remote configured with id '{remote_id}'."));
+    };
+
+    remotes
+        .into_iter()
+        .find(|entry| entry.get("id").and_then(|v| v.as_str()) == Some(remote_id))
+        .ok_or_else(|| format!("No S3 remote configured with id '{remote_id}'."))
+        .and_then(|entry| serde_json::from_value(entry).map_err(|err| err.to_string()))
+}
+
+async fn load_s3_remote_credentials(
+    secrets_manager: &SecretsManager,
+    remote_id: &str,
+) -> Result<(String, String), String> {
+    let access_key = secrets_manager
+        .get_api_key(&s3_access_key_provider(remote_id))
+        .await?
+        .ok_or_else(|| format!("No access key saved for remote '{remote_id}'."))?;
+    let secret_key = secrets_manager
+        .get_api_key(&s3_secret_key_provider(remote_id))
+        .await?
+        .ok_or_else(|| format!("No secret key saved for remote '{remote_id}'."))?;
+    Ok((access_key, secret_key))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts a key of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn sigv4_signing_key(secret_key: &str, date_stamp: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Signs an S3 request with AWS Signature V4 and returns the headers to
+/// send alongside it (Host, x-amz-date, x-amz-content-sha256, Authorization).
+fn sign_s3_request(
+    method: &str,
+    config: &S3RemoteConfig,
+    access_key: &str,
+    secret_key: &str,
+    object_key: &str,
+    payload: &[u8],
+) -> Result<(String, Vec<(String, String)>), String> {
+    let endpoint = config.endpoint.trim_end_matches('/');
+    let host = endpoint
+        .split("://")
+        .nth(1)
+        .ok_or_else(|| format!("Invalid S3 endpoint '{}'.", config.endpoint))?
+        .to_string();
+    let url = format!("{endpoint}/{}/{object_key}", config.bucket);
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let payload_hash = sha256_hex(payload);
+
+    let canonical_uri = format!("/{}/{object_key}", config.bucket);
+    let canonical_headers = format!(
+        "host:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n"
+    );
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{method}\n{canonical_uri}\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}"
+    );
+
+    let credential_scope = format!("{date_stamp}/{}/s3/aws4_request", config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{credential_scope}\n{}",
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let signing_key = sigv4_signing_key(secret_key, &date_stamp, &config.region, "s3");
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={access_key}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}"
+    );
+
+    Ok((
+        url,
+        vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash),
+            ("x-amz-date".to_string(), amz_date),
+            ("authorization".to_string(), authorization),
+        ],
+    ))
+}
+
+async fn s3_put_object(
+    config: &S3RemoteConfig,
+    access_key: &str,
+    secret_key: &str,
+    object_key: &str,
+    body: Vec<u8>,
+) -> Result<(), String> {
+    let (url, headers) = sign_s3_request("PUT", config, access_key, secret_key, object_key, &body)?;
+    let client = HttpClient::new();
+    let mut request = client.put(url).body(body);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request
+        .send()
+        .await
+        .map_err(|err| err.to_string())?
+        .error_for_status()
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+async fn s3_get_object(
+    config: &S3RemoteConfig,
+    access_key: &str,
+    secret_key: &str,
+    object_key: &str,
+) -> Result<Vec<u8>, String> {
+    let (url, headers) = sign_s3_request("GET", config, access_key, secret_key, object_key, b"")?;
+    let client = HttpClient::new();
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let bytes = request
+        .send()
+        .await
+        .map_err(|err| err.to_string())?
+        .error_for_status()
+        .map_err(|err| err.to_string())?
+        .bytes()
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+fn s3_snapshot_key(prefix: &str) -> String {
+    format!("{}/terms.json", prefix.trim_matches('/'))
+}
+
+/// Uploads the full termbase as a JSON snapshot under the remote's
+/// configured prefix, so `sync_pull` (from this or another device) can
+/// merge it back in.
+#[tauri::command]
+async fn sync_push(
+    remote_id: String,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let config = load_s3_remote_config(&app, &remote_id)?;
+    let (access_key, secret_key) = load_s3_remote_credentials(&secrets_manager, &remote_id).await?;
+
+    let terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+    let snapshot = serde_json::to_vec(&terms).map_err(|err| err.to_string())?;
+
+    s3_put_object(
+        &config,
+        &access_key,
+        &secret_key,
+        &s3_snapshot_key(&config.prefix),
+        snapshot,
+    )
+    .await
+}
+
+/// Downloads the latest termbase snapshot from the remote and merges it
+/// into the local database by term name: an incoming term overwrites the
+/// local one only if it has a higher `review_stage`, or an equal stage
+/// with a newer `last_reviewed_at`; terms absent locally are inserted.
+#[tauri::command]
+async fn sync_pull(
+    remote_id: String,
+    app: tauri::AppHandle,
+    secrets_manager: State<'_, SecretsManager>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let config = load_s3_remote_config(&app, &remote_id)?;
+    let (access_key, secret_key) = load_s3_remote_credentials(&secrets_manager, &remote_id).await?;
+
+    let snapshot = s3_get_object(
+        &config,
+        &access_key,
+        &secret_key,
+        &s3_snapshot_key(&config.prefix),
+    )
+    .await?;
+    let remote_terms: Vec<Term> = serde_json::from_slice(&snapshot).map_err(|err| err.to_string())?;
+
+    let local_terms = lexai_core::load_terms_sorted(&state.pool().await).await?;
+    let local_by_name: HashMap<String, &Term> = local_terms
+        .iter()
+        .map(|term| (term.term.to_lowercase(), term))
+        .collect();
+
+    let mut merged = 0usize;
+    for remote_term in &remote_terms {
+        match local_by_name.get(&remote_term.term.to_lowercase()) {
+            Some(local_term) => {
+                let remote_is_newer = remote_term.review_stage > local_term.review_stage
+                    || (remote_term.review_stage == local_term.review_stage
+                        && remote_term.last_reviewed_at > local_term.last_reviewed_at);
+                if remote_is_newer {
+                    sqlx::query(
+                        "UPDATE terms SET definition = ?, definition_cn = ?, review_stage = ?, last_reviewed_at = ?, ease_factor = ?, interval_days = ?, repetitions = ?, next_review_at = ? WHERE id = ?",
+                    )
+                    .bind(&remote_term.definition)
+                    .bind(&remote_term.definition_cn)
+                    .bind(remote_term.review_stage)
+                    .bind(&remote_term.last_reviewed_at)
+                    .bind(remote_term.ease_factor)
+                    .bind(remote_term.interval_days)
+                    .bind(remote_term.repetitions)
+                    .bind(&remote_term.next_review_at)
+                    .bind(local_term.id)
+                    .execute(&state.pool().await)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                    merged += 1;
+                }
+            }
+            None => {
+                sqlx::query(
+                    "INSERT INTO terms (term, definition, definition_cn, review_stage, last_reviewed_at, ease_factor, interval_days, repetitions, next_review_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&remote_term.term)
+                .bind(&remote_term.definition)
+                .bind(&remote_term.definition_cn)
+                .bind(remote_term.review_stage)
+                .bind(&remote_term.last_reviewed_at)
+                .bind(remote_term.ease_factor)
+                .bind(remote_term.interval_days)
+                .bind(remote_term.repetitions)
+                .bind(&remote_term.next_review_at)
+                .execute(&state.pool().await)
+                .await
+                .map_err(|err| err.to_string())?;
+                merged += 1;
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -1659,40 +3557,57 @@ pub fn run() {
 
             eprintln!("[Tauri Setup] Created data directory");
 
-            let db_path = data_dir.join("lexai.db");
             let stronghold_path = data_dir.join(STRONGHOLD_SNAPSHOT);
-            let master_key = hash(b"lexai-default-master-password");
-
-            // Check if this is first-time initialization
-            let is_first_init = !stronghold_path.exists();
-            if is_first_init {
-                eprintln!("[Tauri Setup] First-time Stronghold initialization (this takes ~10 seconds due to key derivation)...");
-            } else {
-                eprintln!("[Tauri Setup] Loading existing Stronghold...");
-            }
-
-            let stronghold = Stronghold::new(&stronghold_path, master_key.as_bytes().to_vec())
-                .map_err(|err| -> Box<dyn Error> { Box::new(err) })?;
-
-            eprintln!("[Tauri Setup] Stronghold ready");
-
-            let secrets_inner = StrongholdInner {
-                stronghold,
-                client_path: STRONGHOLD_CLIENT_PATH.to_vec(),
-            };
-            let secrets_manager = SecretsManager::new(secrets_inner);
-
-            migrate_legacy_api_keys(app, &secrets_manager)
-                .map_err(|err| -> Box<dyn Error> { Box::new(std::io::Error::other(err)) })?;
-
-            app.manage(secrets_manager);
+            let salt_path = data_dir.join(STRONGHOLD_SALT_FILE);
+
+            let profiles =
+                load_profiles(app.handle()).map_err(|err| -> Box<dyn Error> { err.into() })?;
+            // If more than one profile exists we still have to `.manage()`
+            // something (Tauri's `State<T>` extractor panics otherwise), so
+            // fall back to the first profile and let the frontend prompt the
+            // user via the emitted selection event before trusting it.
+            let active_profile = profiles
+                .first()
+                .cloned()
+                .unwrap_or_else(|| Profile {
+                    id: DEFAULT_PROFILE_ID.to_string(),
+                    name: "Default".to_string(),
+                });
+            let db_path = profile_db_path(&data_dir, &active_profile.id);
+
+            // The vault starts locked on every launch; Stronghold isn't
+            // opened until the user unlocks it with their master
+            // passphrase via the `unlock_vault` command.
+            eprintln!("[Tauri Setup] Secrets vault locked, awaiting unlock_vault...");
+            app.manage(SecretsManager::locked(
+                stronghold_path,
+                salt_path,
+                profile_client_path(&active_profile.id),
+            ));
 
-            let pool = tauri::async_runtime::block_on(init_database(&db_path))
+            let pool = tauri::async_runtime::block_on(lexai_core::init_database(&db_path))
                 .map_err(|err| -> Box<dyn Error> { Box::new(err) })?;
-            app.manage(AppState { pool });
+            app.manage(AppState::new(pool));
             app.manage(RpcManager::new());
             app.manage(BatchState::default());
 
+            if profiles.len() > 1 {
+                let _ = app.emit(EVT_PROFILE_SELECT_REQUIRED, &profiles);
+            }
+
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    loop {
+                        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+                        app_handle
+                            .state::<RpcManager>()
+                            .reap_if_dead(&app_handle)
+                            .await;
+                    }
+                });
+            }
+
             if let Some(window) = app.get_webview_window("main") {
                 let manager_handle = app.state::<RpcManager>().client_handle();
                 let shutdown_handle = manager_handle.clone();
@@ -1712,6 +3627,8 @@ pub fn run() {
             fetch_backend_status,
             fetch_backend_health,
             fetch_backend_diagnostics,
+            fetch_backend_resource_diagnostics,
+            fetch_backend_capabilities,
             restart_backend,
             open_logs_dir,
             search_term_contexts,
@@ -1720,6 +3637,9 @@ pub fn run() {
             add_term,
             get_all_terms,
             find_term_by_name,
+            search_terms,
+            embed_all_terms,
+            semantic_search_terms,
             delete_term,
             update_term,
             export_terms_csv,
@@ -1730,65 +3650,24 @@ pub fn run() {
             save_api_key,
             get_api_key,
             has_api_key,
-            fetch_backend_health
+            unlock_vault,
+            is_vault_unlocked,
+            change_master_passphrase,
+            create_backup,
+            restore_backup,
+            save_s3_remote,
+            sync_push,
+            sync_pull,
+            fetch_backend_health,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            delete_profile
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-fn build_anki_back_field(definition: &str, definition_cn: Option<&str>) -> String {
-    let mut content = encode_html(definition);
-    content = content.replace('\n', "<br>");
-
-    if let Some(def_cn) = definition_cn {
-        if !def_cn.trim().is_empty() {
-            let mut cn = encode_html(def_cn);
-            cn = cn.replace('\n', "<br>");
-            content.push_str("<br><div class=\"definition-cn\">");
-            content.push_str(&cn);
-            content.push_str("</div>");
-        }
-    }
-
-    content
-}
-
-fn encode_html(value: &str) -> String {
-    let mut escaped = String::with_capacity(value.len());
-    for ch in value.chars() {
-        match ch {
-            '&' => escaped.push_str("&amp;"),
-            '<' => escaped.push_str("&lt;"),
-            '>' => escaped.push_str("&gt;"),
-            '"' => escaped.push_str("&quot;"),
-            '\'' => escaped.push_str("&#39;"),
-            _ => escaped.push(ch),
-        }
-    }
-    escaped
-}
-
-fn load_pdf_font_family() -> Result<FontFamily<FontData>, String> {
-    let font_bytes = include_bytes!("../resources/fonts/DejaVuSans.ttf");
-    let load = |data: &[u8]| {
-        FontData::new(data.to_vec(), None).map_err(|err| format!("Failed to load font: {err}"))
-    };
-
-    Ok(FontFamily {
-        regular: load(font_bytes)?,
-        bold: load(font_bytes)?,
-        italic: load(font_bytes)?,
-        bold_italic: load(font_bytes)?,
-    })
-}
-
-fn sanitize_pdf_text(value: &str) -> String {
-    value
-        .replace("\r\n", "\n")
-        .replace('\r', "\n")
-        .replace('\t', "    ")
-}
-
 #[tauri::command]
 async fn open_logs_dir(app: tauri::AppHandle) -> Result<bool, String> {
     let logs_dir = app
@@ -1830,59 +3709,8 @@ async fn open_logs_dir(app: tauri::AppHandle) -> Result<bool, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::Row;
     use tempfile::tempdir;
 
-    async fn setup_pool() -> (tempfile::TempDir, SqlitePool) {
-        let dir = tempdir().unwrap();
-        let db_path = dir.path().join("lexai.db");
-        let pool = init_database(&db_path).await.unwrap();
-        (dir, pool)
-    }
-
-    #[tokio::test]
-    async fn submit_review_result_updates_stage_and_timestamp() {
-        let (_dir, pool) = setup_pool().await;
-
-        sqlx::query("INSERT INTO terms (term, definition, definition_cn) VALUES (?, ?, ?)")
-            .bind("Neural Network")
-            .bind("An interconnected group of nodes.")
-            .bind(Option::<String>::None)
-            .execute(&pool)
-            .await
-            .unwrap();
-
-        let row = sqlx::query("SELECT id FROM terms WHERE term = ?")
-            .bind("Neural Network")
-            .fetch_one(&pool)
-            .await
-            .unwrap();
-        let id: i64 = row.get("id");
-
-        apply_review_result(&pool, id, true).await.unwrap();
-        let first = sqlx::query("SELECT review_stage, last_reviewed_at FROM terms WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
-            .await
-            .unwrap();
-        let stage_after_known: i64 = first.get("review_stage");
-        let ts_first: String = first.get("last_reviewed_at");
-        assert_eq!(stage_after_known, 1);
-        assert!(!ts_first.is_empty());
-
-        apply_review_result(&pool, id, false).await.unwrap();
-        let second = sqlx::query("SELECT review_stage, last_reviewed_at FROM terms WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
-            .await
-            .unwrap();
-        let stage_after_unknown: i64 = second.get("review_stage");
-        let ts_second: String = second.get("last_reviewed_at");
-        assert_eq!(stage_after_unknown, 0);
-        assert!(!ts_second.is_empty());
-        assert!(ts_second >= ts_first);
-    }
-
     #[tokio::test]
     async fn secrets_manager_persists_and_clears_keys() {
         let dir = tempdir().unwrap();
@@ -1890,7 +3718,7 @@ mod tests {
         let master_key = hash(b"test-master-password");
         let stronghold = Stronghold::new(&snapshot_path, master_key.as_bytes().to_vec()).unwrap();
 
-        let secrets = SecretsManager::new(StrongholdInner {
+        let secrets = SecretsManager::from_unlocked(StrongholdInner {
             stronghold,
             client_path: b"test-client".to_vec(),
         });
@@ -1909,4 +3737,117 @@ mod tests {
         assert_eq!(secrets.get_api_key("openai").await.unwrap(), None);
         assert!(!secrets.has_api_key("openai").await.unwrap());
     }
+
+    #[tokio::test]
+    async fn secrets_manager_gates_access_until_unlocked_and_rekeys() {
+        let dir = tempdir().unwrap();
+        let stronghold_path = dir.path().join("stronghold.scout");
+        let salt_path = dir.path().join("stronghold.salt");
+
+        let vault = SecretsManager::locked(
+            stronghold_path.clone(),
+            salt_path.clone(),
+            STRONGHOLD_CLIENT_PATH.to_vec(),
+        );
+        assert!(!vault.is_unlocked().await);
+        assert!(vault.get_api_key("openai").await.is_err());
+
+        vault.unlock("correct horse battery staple").await.unwrap();
+        assert!(vault.is_unlocked().await);
+        vault.save_api_key("openai", "sk-test-123").await.unwrap();
+        assert_eq!(
+            vault.get_api_key("openai").await.unwrap(),
+            Some("sk-test-123".to_string())
+        );
+
+        // A fresh manager pointed at the same files rejects the wrong passphrase.
+        let reopened = SecretsManager::locked(
+            stronghold_path.clone(),
+            salt_path.clone(),
+            STRONGHOLD_CLIENT_PATH.to_vec(),
+        );
+        assert!(reopened.unlock("wrong passphrase").await.is_err());
+
+        vault
+            .rekey(
+                "correct horse battery staple",
+                "new passphrase",
+                &[STRONGHOLD_CLIENT_PATH.to_vec()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            vault.get_api_key("openai").await.unwrap(),
+            Some("sk-test-123".to_string())
+        );
+
+        let after_rekey = SecretsManager::locked(
+            stronghold_path,
+            salt_path,
+            STRONGHOLD_CLIENT_PATH.to_vec(),
+        );
+        assert!(after_rekey
+            .unlock("correct horse battery staple")
+            .await
+            .is_err());
+        after_rekey.unlock("new passphrase").await.unwrap();
+        assert_eq!(
+            after_rekey.get_api_key("openai").await.unwrap(),
+            Some("sk-test-123".to_string())
+        );
+    }
+
+    fn term(id: i64, text: &str) -> Term {
+        Term {
+            id,
+            term: text.to_string(),
+            definition: String::new(),
+            definition_cn: None,
+            review_stage: 0,
+            last_reviewed_at: None,
+            ease_factor: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            next_review_at: None,
+        }
+    }
+
+    #[test]
+    fn fuzzy_search_terms_ranks_exact_prefix_then_fuzzy() {
+        let terms = vec![
+            term(1, "Neural Network"),
+            term(2, "Neural Networks"),
+            term(3, "Neutral Network"),
+            term(4, "Convolution"),
+        ];
+
+        let results = fuzzy_search_terms(&terms, "Neural Network", 10);
+        assert_eq!(results[0].id, 1, "exact match should rank first");
+
+        let prefix_results = fuzzy_search_terms(&terms, "Neural", 10);
+        assert_eq!(
+            prefix_results.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![1, 2],
+            "prefix matches should be found and sorted by closeness"
+        );
+
+        let typo_results = fuzzy_search_terms(&terms, "Nueral Network", 10);
+        assert!(
+            typo_results.iter().any(|t| t.id == 1),
+            "a single-character transposition within budget should still match"
+        );
+        assert!(
+            !typo_results.iter().any(|t| t.id == 4),
+            "unrelated terms outside the typo budget should not match"
+        );
+    }
+
+    #[test]
+    fn bounded_levenshtein_matches_unbounded_distance() {
+        let a: Vec<char> = "kitten".chars().collect();
+        let b: Vec<char> = "sitting".chars().collect();
+        assert_eq!(levenshtein_distance(&a, &b), 3);
+        assert_eq!(bounded_levenshtein(&a, &b, 3), Some(3));
+        assert_eq!(bounded_levenshtein(&a, &b, 2), None);
+    }
 }